@@ -0,0 +1,338 @@
+use crate::Word;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::iter::FromIterator;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The words of a game: the answers which may be chosen, and the usually much
+/// larger set of words accepted as guesses. Every answer is also a valid guess.
+/// Answers may be weighted by how likely they are to be chosen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dictionary {
+    answers: HashSet<Word>,
+    guesses: HashSet<Word>,
+    /// Relative likelihood of each answer, those not listed have a weight of 1
+    weights: HashMap<Word, f64>,
+}
+
+impl Dictionary {
+    /// The list of words in `res/words.txt`, built into the crate.
+    const EMBEDDED: &'static str = include_str!("../res/words.txt");
+
+    /// Create a dictionary from the possible answers and the other words accepted as guesses.
+    pub fn new<A, G>(answers: A, guesses: G) -> Self
+    where
+        A: IntoIterator<Item = Word>,
+        G: IntoIterator<Item = Word>,
+    {
+        let answers: HashSet<Word> = answers.into_iter().collect();
+        let mut guesses: HashSet<Word> = guesses.into_iter().collect();
+        guesses.extend(answers.iter().copied());
+        Self {
+            answers,
+            guesses,
+            weights: HashMap::new(),
+        }
+    }
+
+    /// The dictionary built into the crate, in which every word may be the answer.
+    pub fn embedded() -> Self {
+        Self::EMBEDDED
+            .parse()
+            .expect("The embedded dictionary is well formed")
+    }
+
+    /// Load a list of words from a file, in the format described by `read_from`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::read_from(File::open(path)?)
+    }
+
+    /// Read a list of words, one per line, each of which may be the answer. Blank
+    /// lines and lines starting with `#` are ignored and repeated words are dropped.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, Error> {
+        read_lines(reader, Word::try_from_str).map(|words| words.into_iter().collect())
+    }
+
+    /// Load answer weights from a file, in the format described by `read_weights`.
+    pub fn load_weights<P: AsRef<Path>>(self, path: P) -> Result<Self, Error> {
+        self.read_weights(File::open(path)?)
+    }
+
+    /// Read answer weights, one word and its weight separated by whitespace on each
+    /// line. Weights are relative and must be positive, answers which are not listed
+    /// have a weight of 1. Blank lines and lines starting with `#` are ignored.
+    pub fn read_weights<R: Read>(self, reader: R) -> Result<Self, Error> {
+        let weights = read_lines(reader, |line| {
+            let mut tokens = line.split_whitespace();
+            let word = Word::try_from_str(tokens.next()?)?;
+            let weight: f64 = tokens.next()?.parse().ok()?;
+            if tokens.next().is_some() || !is_valid_weight(weight) {
+                return None;
+            }
+            Some((word, weight))
+        })?;
+        self.with_weights(weights)
+    }
+
+    /// Set the relative likelihood of answers being chosen. Weights must be positive
+    /// and finite.
+    pub fn with_weights<I: IntoIterator<Item = (Word, f64)>>(
+        mut self,
+        weights: I,
+    ) -> Result<Self, Error> {
+        for (word, weight) in weights {
+            if !is_valid_weight(weight) {
+                return Err(Error::InvalidWeight { word, weight });
+            }
+            self.weights.insert(word, weight);
+        }
+        Ok(self)
+    }
+
+    /// The relative likelihood of `word` being chosen as the answer, if it is one.
+    pub fn weight(&self, word: &Word) -> f64 {
+        if !self.is_answer(word) {
+            return 0.0;
+        }
+        self.weights.get(word).copied().unwrap_or(1.0)
+    }
+
+    /// Also accept `guesses` as guesses, without them becoming possible answers.
+    pub fn with_guesses<I: IntoIterator<Item = Word>>(mut self, guesses: I) -> Self {
+        self.guesses.extend(guesses);
+        self
+    }
+
+    /// The number of possible answers.
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// Iterate over the possible answers, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Word> + '_ {
+        self.answers.iter()
+    }
+
+    /// The word spelled by `s`, if it is accepted as a guess.
+    pub fn lookup(&self, s: &str) -> Option<Word> {
+        Word::try_from_str(s).filter(|w| self.is_guess(w))
+    }
+
+    /// Words which may be the answer.
+    pub fn answers(&self) -> &HashSet<Word> {
+        &self.answers
+    }
+
+    /// Words which are accepted as guesses, including all of the answers.
+    pub fn guesses(&self) -> &HashSet<Word> {
+        &self.guesses
+    }
+
+    /// Words accepted as guesses which can never be the answer, only useful for
+    /// the information they reveal.
+    pub fn probes(&self) -> impl Iterator<Item = &Word> + '_ {
+        self.guesses.difference(&self.answers)
+    }
+
+    pub fn is_answer(&self, word: &Word) -> bool {
+        self.answers.contains(word)
+    }
+
+    pub fn is_guess(&self, word: &Word) -> bool {
+        self.guesses.contains(word)
+    }
+}
+
+/// A dictionary in which every accepted guess may also be the answer.
+impl From<HashSet<Word>> for Dictionary {
+    fn from(words: HashSet<Word>) -> Self {
+        Self {
+            answers: words.clone(),
+            guesses: words,
+            weights: HashMap::new(),
+        }
+    }
+}
+
+impl FromIterator<Word> for Dictionary {
+    fn from_iter<I: IntoIterator<Item = Word>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<HashSet<Word>>())
+    }
+}
+
+impl FromStr for Dictionary {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::read_from(s.as_bytes())
+    }
+}
+
+/// Whether `weight` can be the relative likelihood of an answer.
+pub(crate) fn is_valid_weight(weight: f64) -> bool {
+    weight.is_finite() && weight > 0.0
+}
+
+/// Parse each line which is not blank or a comment with `parse`, collecting
+/// the numbers of any lines it fails on.
+fn read_lines<R, T, F>(reader: R, parse: F) -> Result<Vec<T>, Error>
+where
+    R: Read,
+    F: Fn(&str) -> Option<T>,
+{
+    let mut items = Vec::new();
+    let mut malformed = Vec::new();
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse(line) {
+            Some(item) => items.push(item),
+            None => malformed.push(i + 1),
+        }
+    }
+    if malformed.is_empty() {
+        Ok(items)
+    } else {
+        Err(Error::Malformed { lines: malformed })
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// These lines, numbered from 1, could not be parsed
+    Malformed {
+        lines: Vec<usize>,
+    },
+    /// This word was given a weight which is not positive and finite
+    InvalidWeight {
+        word: Word,
+        weight: f64,
+    },
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read dictionary: {}", e),
+            Self::Malformed { lines } => {
+                write!(f, "malformed words on lines")?;
+                for (i, line) in lines.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, line)?;
+                }
+                Ok(())
+            }
+            Self::InvalidWeight { word, weight } => {
+                write!(f, "invalid weight {} for {}", weight, word)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::{self, Dictionary};
+    use crate::util::words;
+    use crate::Word;
+
+    #[test]
+    fn test_dictionary() {
+        let dict = Dictionary::new(
+            words(&["batch", "catch", "hatch"]),
+            words(&["blimp", "catch"]),
+        );
+        assert_eq!(dict.answers().len(), 3);
+        assert_eq!(dict.guesses().len(), 4);
+        assert!(dict.is_answer(&Word::try_from_str("hatch").unwrap()));
+        assert!(dict.is_guess(&Word::try_from_str("hatch").unwrap()));
+        assert!(!dict.is_answer(&Word::try_from_str("blimp").unwrap()));
+        assert!(dict.is_guess(&Word::try_from_str("blimp").unwrap()));
+        assert!(dict.probes().eq(words(&["blimp"]).iter()));
+
+        let same: Dictionary = words(&["batch", "catch"]).into_iter().collect();
+        assert_eq!(same.answers(), same.guesses());
+        assert_eq!(same.probes().count(), 0);
+    }
+
+    #[test]
+    fn test_load_dictionary() {
+        let embedded = Dictionary::embedded();
+        assert_eq!(embedded.len(), 2315);
+        assert_eq!(embedded.answers(), embedded.guesses());
+        assert_eq!(Dictionary::load("./res/words.txt").unwrap(), embedded);
+        assert!(embedded.iter().all(|w| embedded.is_answer(w)));
+        assert_eq!(embedded.lookup("zonal"), Word::try_from_str("zonal"));
+        assert_eq!(embedded.lookup("zzzzz"), None);
+        assert_eq!(embedded.lookup("zonally"), None);
+
+        let text = "# answers\nbatch\r\n\n  catch\nbatch\n";
+        let dict: Dictionary = text.parse().unwrap();
+        assert_eq!(dict.len(), 2);
+        assert!(!dict.is_empty());
+
+        let dict = dict.with_guesses(words(&["blimp"]));
+        assert_eq!(dict.len(), 2);
+        assert!(dict.lookup("blimp").is_some());
+
+        let text = "batch\nc4tch\nhatch\nlatches\n";
+        match text.parse::<Dictionary>() {
+            Err(dictionary::Error::Malformed { lines }) => assert_eq!(lines, vec![2, 4]),
+            result => panic!("{:?}", result),
+        }
+
+        assert!(matches!(
+            Dictionary::load("./res/missing.txt"),
+            Err(dictionary::Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_weights() {
+        let text = "# word weight\nbatch 0.5\ncatch 2e1\nblimp 3\n";
+        let dict = Dictionary::new(words(&["batch", "catch", "hatch"]), words(&["blimp"]))
+            .read_weights(text.as_bytes())
+            .unwrap();
+        let weight = |s| dict.weight(&Word::try_from_str(s).unwrap());
+        assert_eq!(weight("batch"), 0.5);
+        assert_eq!(weight("catch"), 20.0);
+        assert_eq!(weight("hatch"), 1.0);
+        // Words which cannot be the answer are never chosen
+        assert_eq!(weight("blimp"), 0.0);
+
+        let text = "batch 1\ncatch\nhatch -1\nlatch one\nmatch 1 2\npatch 1e400\nwatch 0.1\n";
+        match dict.clone().read_weights(text.as_bytes()) {
+            Err(dictionary::Error::Malformed { lines }) => {
+                assert_eq!(lines, vec![2, 3, 4, 5, 6])
+            }
+            result => panic!("{:?}", result),
+        }
+
+        // Weights set directly are checked the same way as those read
+        let batch = Word::try_from_str("batch").unwrap();
+        for weight in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            match dict.clone().with_weights(vec![(batch, weight)]) {
+                Err(dictionary::Error::InvalidWeight { word, .. }) => assert_eq!(word, batch),
+                result => panic!("{} accepted: {:?}", weight, result),
+            }
+        }
+    }
+}
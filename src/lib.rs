@@ -1,7 +1,13 @@
 use std::fmt;
 
+pub mod dictionary;
+pub mod matrix;
+pub mod packed;
+pub mod rules;
 pub mod server;
 pub mod solver;
+pub mod strategy;
+pub mod tree;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LetterOutcome {
@@ -18,7 +24,104 @@ impl Default for LetterOutcome {
 
 pub type GuessOutcome = [LetterOutcome; 5];
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// Write `outcome` with one character per letter: `*` correct, `+` present, `-` absent.
+pub fn outcome_to_str(outcome: &GuessOutcome) -> String {
+    outcome
+        .iter()
+        .map(|l| match l {
+            LetterOutcome::Correct => '*',
+            LetterOutcome::Present => '+',
+            LetterOutcome::Absent => '-',
+        })
+        .collect()
+}
+
+/// Parse an outcome written as by [`outcome_to_str`].
+pub fn outcome_from_str(s: &str) -> Option<GuessOutcome> {
+    if s.len() != 5 {
+        return None;
+    }
+    let mut outcome = GuessOutcome::default();
+    for (i, b) in s.bytes().enumerate() {
+        outcome[i] = match b {
+            b'*' => LetterOutcome::Correct,
+            b'+' => LetterOutcome::Present,
+            b'-' => LetterOutcome::Absent,
+            _ => return None,
+        };
+    }
+    Some(outcome)
+}
+
+/// A `GuessOutcome` packed into a number in `0..243`, with each letter's outcome
+/// as a base 3 digit (absent = 0, present = 1, correct = 2), first letter most significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pattern(u8);
+
+impl Pattern {
+    /// The number of distinct patterns
+    pub const COUNT: usize = 243;
+
+    /// The pattern of a correct guess
+    pub const WIN: Self = Self(242);
+
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < Self::COUNT {
+            Some(Self(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn is_win(self) -> bool {
+        self.0 == Self::WIN.0
+    }
+
+    /// Iterate over every pattern in order of their indices.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::COUNT as u8).map(Self)
+    }
+
+    pub fn outcome(self) -> GuessOutcome {
+        let mut result = GuessOutcome::default();
+        let mut n = self.0;
+        for r in result.iter_mut().rev() {
+            *r = match n % 3 {
+                0 => LetterOutcome::Absent,
+                1 => LetterOutcome::Present,
+                _ => LetterOutcome::Correct,
+            };
+            n /= 3;
+        }
+        result
+    }
+}
+
+impl From<GuessOutcome> for Pattern {
+    fn from(outcome: GuessOutcome) -> Self {
+        let n = outcome.iter().fold(0, |acc, l| {
+            let digit = match l {
+                LetterOutcome::Absent => 0,
+                LetterOutcome::Present => 1,
+                LetterOutcome::Correct => 2,
+            };
+            3 * acc + digit
+        });
+        Self(n)
+    }
+}
+
+impl From<Pattern> for GuessOutcome {
+    fn from(pattern: Pattern) -> Self {
+        pattern.outcome()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Letter(u8);
 
 impl Letter {
@@ -84,7 +187,7 @@ impl fmt::Debug for Letter {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Word([Letter; 5]);
 
 impl Word {
@@ -125,6 +228,15 @@ impl Word {
     }
 }
 
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for l in self.iter() {
+            write!(f, "{}", l.0 as char)?;
+        }
+        Ok(())
+    }
+}
+
 impl IntoIterator for Word {
     type Item = Letter;
 
@@ -135,7 +247,44 @@ impl IntoIterator for Word {
     }
 }
 
+/// Compute the outcome the game reports when `guess` is played against `answer`.
+pub fn score(guess: Word, answer: Word) -> GuessOutcome {
+    let mut result = [LetterOutcome::Absent; 5];
+    // Count the letters of the answer which are not matched by a correct letter
+    let mut unmatched = [0u8; 26];
+    for (i, (x, y)) in guess.iter().zip(answer.iter()).enumerate() {
+        if x == y {
+            result[i] = LetterOutcome::Correct;
+        } else {
+            unmatched[y.index() as usize] += 1;
+        }
+    }
+    // Unmatched letters are present for the first guessed letters which need them,
+    // any further copies in the guess are absent
+    for (i, x) in guess.iter().enumerate() {
+        let j = x.index() as usize;
+        if result[i] != LetterOutcome::Correct && unmatched[j] > 0 {
+            unmatched[j] -= 1;
+            result[i] = LetterOutcome::Present;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
 pub(crate) mod util {
+    use crate::Word;
+
+    /// Parse `s` as a word, for writing single words in tests.
+    pub(crate) fn word(s: &str) -> Word {
+        Word::try_from_str(s).unwrap()
+    }
+
+    /// Parse each of `xs` as a word, for writing word lists in tests.
+    pub(crate) fn words(xs: &[&str]) -> Vec<Word> {
+        xs.iter().map(|s| word(s)).collect()
+    }
+
     pub(crate) fn map_array<T, U, F, const N: usize>(xs: [T; N], f: F) -> [U; N]
     where
         T: Sized,
@@ -152,7 +301,10 @@ pub(crate) mod util {
 
 #[cfg(test)]
 mod tests {
-    use crate::{util, Letter, Word};
+    use crate::{
+        outcome_from_str, outcome_to_str, score, util, GuessOutcome, Letter, LetterOutcome,
+        Pattern, Word,
+    };
 
     #[test]
     fn test_letters() {
@@ -198,4 +350,75 @@ mod tests {
         // Numbers don't parse into letters
         assert_eq!(Word::try_from_str("ABCD1"), None,);
     }
+
+    #[test]
+    fn test_pattern() {
+        for (i, p) in Pattern::all().enumerate() {
+            assert_eq!(p.index(), i);
+            assert_eq!(Pattern::from_index(i), Some(p));
+            assert_eq!(Pattern::from(p.outcome()), p);
+            assert_eq!(p.is_win(), i == 242);
+        }
+        assert_eq!(Pattern::all().count(), Pattern::COUNT);
+        assert_eq!(Pattern::from_index(Pattern::COUNT), None);
+
+        assert_eq!(Pattern::from([LetterOutcome::Absent; 5]).index(), 0);
+        assert_eq!(Pattern::from([LetterOutcome::Correct; 5]), Pattern::WIN);
+        let outcome: GuessOutcome = Pattern::WIN.into();
+        assert_eq!(outcome, [LetterOutcome::Correct; 5]);
+        let mut outcome = [LetterOutcome::Absent; 5];
+        outcome[4] = LetterOutcome::Present;
+        assert_eq!(Pattern::from(outcome).index(), 1);
+        outcome[0] = LetterOutcome::Correct;
+        assert_eq!(Pattern::from(outcome).index(), 2 * 81 + 1);
+    }
+
+    #[test]
+    fn test_outcome_str() {
+        for p in Pattern::all() {
+            let s = outcome_to_str(&p.outcome());
+            assert_eq!(outcome_from_str(&s), Some(p.outcome()));
+        }
+        assert_eq!(
+            outcome_to_str(&score(
+                Word::try_from_str("geese").unwrap(),
+                Word::try_from_str("sheep").unwrap()
+            )),
+            "-+*+-"
+        );
+        for s in ["", "****", "******", "**x**", "!"] {
+            assert_eq!(outcome_from_str(s), None);
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        use LetterOutcome::{Absent, Correct, Present};
+        let score_str = |guess, answer| {
+            score(
+                Word::try_from_str(guess).unwrap(),
+                Word::try_from_str(answer).unwrap(),
+            )
+        };
+
+        assert_eq!(
+            score_str("river", "trees"),
+            [Present, Absent, Absent, Correct, Absent]
+        );
+        assert_eq!(score_str("trees", "trees"), [Correct; 5]);
+        // Only as many copies of a letter as the answer has can be present
+        assert_eq!(
+            score_str("cacao", "whack"),
+            [Present, Present, Absent, Absent, Absent]
+        );
+        // Correct letters take priority over earlier present ones
+        assert_eq!(
+            score_str("clack", "whack"),
+            [Absent, Absent, Correct, Correct, Correct]
+        );
+        assert_eq!(
+            score_str("geese", "sheep"),
+            [Absent, Present, Correct, Present, Absent]
+        );
+    }
 }
@@ -0,0 +1,452 @@
+use crate::{score, Letter, Pattern, Word};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The pattern of every guess played against every answer, so that strategies
+/// can look outcomes up by word index instead of scoring words again and again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatrix {
+    guesses: Vec<Word>,
+    answers: Vec<Word>,
+    /// Index into `answers` of each guess, if it is also an answer
+    guess_answers: Vec<Option<usize>>,
+    /// Row major, one row of answers per guess
+    patterns: Vec<Pattern>,
+    /// For each guess, the answer indices grouped by the pattern they produce
+    grouped: Vec<u16>,
+    /// For each guess, where the answers with each pattern start in `grouped`
+    group_starts: Vec<u32>,
+}
+
+impl PatternMatrix {
+    const MAGIC: &'static [u8; 8] = b"wordlepm";
+    /// The number of distinct five letter words, more than any sorted list can hold
+    const MAX_WORDS: usize = 26 * 26 * 26 * 26 * 26;
+    /// Answer indices are grouped as u16s
+    const MAX_ANSWERS: usize = u16::MAX as usize + 1;
+
+    pub fn new(mut guesses: Vec<Word>, mut answers: Vec<Word>) -> Self {
+        guesses.sort_unstable();
+        guesses.dedup();
+        answers.sort_unstable();
+        answers.dedup();
+        assert!(
+            answers.len() <= Self::MAX_ANSWERS,
+            "Too many answers for a pattern matrix"
+        );
+        let mut patterns = Vec::with_capacity(guesses.len() * answers.len());
+        for guess in guesses.iter() {
+            patterns.extend(answers.iter().map(|a| Pattern::from(score(*guess, *a))));
+        }
+        Self::from_parts(guesses, answers, patterns)
+    }
+
+    /// Build a matrix from sorted words and their patterns, with no more answers
+    /// than `MAX_ANSWERS`.
+    fn from_parts(guesses: Vec<Word>, answers: Vec<Word>, patterns: Vec<Pattern>) -> Self {
+        let guess_answers = guesses
+            .iter()
+            .map(|g| answers.binary_search(g).ok())
+            .collect();
+
+        // Counting sort of each row by pattern
+        let n = answers.len();
+        let mut grouped = vec![0u16; patterns.len()];
+        let mut group_starts = Vec::with_capacity(guesses.len() * (Pattern::COUNT + 1));
+        // Every guess gets a row of starts, even when there are no answers to group
+        for g in 0..guesses.len() {
+            let row = &patterns[(g * n)..((g + 1) * n)];
+            let mut counts = [0u32; Pattern::COUNT];
+            for p in row {
+                counts[p.index()] += 1;
+            }
+            let mut starts = [0u32; Pattern::COUNT + 1];
+            for (i, c) in counts.iter().enumerate() {
+                starts[i + 1] = starts[i] + c;
+            }
+            let mut next = starts;
+            for (a, p) in row.iter().enumerate() {
+                grouped[g * n + next[p.index()] as usize] = a as u16;
+                next[p.index()] += 1;
+            }
+            group_starts.extend_from_slice(&starts);
+        }
+
+        Self {
+            guesses,
+            answers,
+            guess_answers,
+            patterns,
+            grouped,
+            group_starts,
+        }
+    }
+
+    /// All guesses, sorted.
+    pub fn guesses(&self) -> &[Word] {
+        &self.guesses
+    }
+
+    /// All answers, sorted.
+    pub fn answers(&self) -> &[Word] {
+        &self.answers
+    }
+
+    pub fn guess_index(&self, word: &Word) -> Option<usize> {
+        self.guesses.binary_search(word).ok()
+    }
+
+    pub fn answer_index(&self, word: &Word) -> Option<usize> {
+        self.answers.binary_search(word).ok()
+    }
+
+    /// The index into the answers of the guess at index `guess`, if it is also an answer.
+    pub fn guess_answer(&self, guess: usize) -> Option<usize> {
+        self.guess_answers[guess]
+    }
+
+    /// The pattern produced by the guess at index `guess` for the answer at index `answer`.
+    pub fn get(&self, guess: usize, answer: usize) -> Pattern {
+        self.row(guess)[answer]
+    }
+
+    /// The patterns produced by the guess at index `guess` for every answer.
+    pub fn row(&self, guess: usize) -> &[Pattern] {
+        let n = self.answers.len();
+        &self.patterns[(guess * n)..((guess + 1) * n)]
+    }
+
+    /// Indices of the answers for which the guess at index `guess` produces `pattern`, sorted.
+    pub fn answers_with(
+        &self,
+        guess: usize,
+        pattern: Pattern,
+    ) -> impl ExactSizeIterator<Item = usize> + '_ {
+        let starts = &self.group_starts[(guess * (Pattern::COUNT + 1))..];
+        let n = self.answers.len();
+        let start = guess * n + starts[pattern.index()] as usize;
+        let end = guess * n + starts[pattern.index() + 1] as usize;
+        self.grouped[start..end].iter().map(|a| usize::from(*a))
+    }
+
+    /// The set of answers for which the guess at index `guess` produces `pattern`.
+    ///
+    /// The set is built from the cached group on each call rather than precomputed:
+    /// storing a set for every guess and pattern would take one bit per answer for each
+    /// of up to 243 patterns per guess, hundreds of megabytes for the full word list,
+    /// while building one costs a single insert per answer in the group.
+    pub fn answer_set(&self, guess: usize, pattern: Pattern) -> CandidateSet {
+        let mut set = CandidateSet::empty(self.answers.len());
+        for a in self.answers_with(guess, pattern) {
+            set.insert(a);
+        }
+        set
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Write the matrix in a binary format: a magic number, the number of guesses and
+    /// answers as little endian u32s, the guesses and answers as 5 ASCII bytes each,
+    /// then one byte per pattern.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(Self::MAGIC)?;
+        writer.write_all(&(self.guesses.len() as u32).to_le_bytes())?;
+        writer.write_all(&(self.answers.len() as u32).to_le_bytes())?;
+        for word in self.guesses.iter().chain(self.answers.iter()) {
+            writer.write_all(&word.0.map(|l| l.0))?;
+        }
+        let patterns: Vec<u8> = self.patterns.iter().map(|p| p.0).collect();
+        writer.write_all(&patterns)
+    }
+
+    /// Read a matrix in the format written by `write_to`. The sizes in the header are
+    /// not trusted: memory is only allocated as the data it holds is actually read.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(invalid("not a pattern matrix"));
+        }
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let guesses_len = u32::from_le_bytes(len) as usize;
+        reader.read_exact(&mut len)?;
+        let answers_len = u32::from_le_bytes(len) as usize;
+        if guesses_len > Self::MAX_WORDS || answers_len > Self::MAX_WORDS {
+            return Err(invalid("too many words"));
+        }
+        if answers_len > Self::MAX_ANSWERS {
+            return Err(invalid("too many answers"));
+        }
+        let patterns_len = guesses_len
+            .checked_mul(answers_len)
+            .ok_or_else(|| invalid("too many patterns"))?;
+
+        let mut read_words = |n: usize| -> io::Result<Vec<Word>> {
+            let mut words = Vec::new();
+            let mut bytes = [0u8; 5];
+            for _ in 0..n {
+                reader.read_exact(&mut bytes)?;
+                let mut word = [Letter::default(); 5];
+                for (l, b) in word.iter_mut().zip(bytes) {
+                    *l = Letter::new(b).ok_or_else(|| invalid("invalid letter"))?;
+                }
+                words.push(Word(word));
+            }
+            if words.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(invalid("words not sorted"));
+            }
+            Ok(words)
+        };
+        let guesses = read_words(guesses_len)?;
+        let answers = read_words(answers_len)?;
+
+        let mut bytes = Vec::new();
+        reader.take(patterns_len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != patterns_len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let patterns = bytes
+            .into_iter()
+            .map(|b| Pattern::from_index(b as usize).ok_or_else(|| invalid("invalid pattern")))
+            .collect::<io::Result<Vec<Pattern>>>()?;
+
+        Ok(Self::from_parts(guesses, answers, patterns))
+    }
+}
+
+/// A set of indices into the answers of a `PatternMatrix`, stored as a bit set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CandidateSet {
+    blocks: Vec<u64>,
+    capacity: usize,
+}
+
+impl CandidateSet {
+    /// The set containing every index less than `capacity`.
+    pub fn full(capacity: usize) -> Self {
+        let mut blocks = vec![u64::MAX; (capacity + 63) / 64];
+        if capacity % 64 != 0 {
+            if let Some(last) = blocks.last_mut() {
+                *last = (1 << (capacity % 64)) - 1;
+            }
+        }
+        Self { blocks, capacity }
+    }
+
+    /// The empty set which can hold indices less than `capacity`.
+    pub fn empty(capacity: usize) -> Self {
+        Self {
+            blocks: vec![0; (capacity + 63) / 64],
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|b| *b == 0)
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.capacity && self.blocks[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        assert!(index < self.capacity, "Index out of range");
+        self.blocks[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.capacity {
+            self.blocks[index / 64] &= !(1 << (index % 64));
+        }
+    }
+
+    /// Keep only the indices which are also in `other`.
+    pub fn intersect_with(&mut self, other: &Self) {
+        for (a, b) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *a &= b;
+        }
+    }
+
+    pub fn retain<F: FnMut(usize) -> bool>(&mut self, mut f: F) {
+        for i in 0..self.blocks.len() {
+            let mut bits = self.blocks[i];
+            while bits != 0 {
+                let j = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                if !f(64 * i + j) {
+                    self.blocks[i] &= !(1 << j);
+                }
+            }
+        }
+    }
+
+    /// Iterate over the indices in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(i, b)| {
+            let mut bits = *b;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let j = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(64 * i + j)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::matrix::{CandidateSet, PatternMatrix};
+    use crate::util::words;
+    use crate::{score, Pattern, Word};
+    use std::io;
+
+    #[test]
+    fn test_matrix() {
+        let guesses = words(&["river", "abbey", "crave", "kings", "trees"]);
+        let answers = words(&["trees", "great", "abbey"]);
+        let matrix = PatternMatrix::new(guesses.clone(), answers.clone());
+
+        assert_eq!(matrix.guesses().len(), guesses.len());
+        assert_eq!(matrix.answers().len(), answers.len());
+        for g in guesses.iter() {
+            let i = matrix.guess_index(g).unwrap();
+            assert_eq!(matrix.guesses()[i], *g);
+            assert_eq!(matrix.guess_answer(i), matrix.answer_index(g));
+            for a in answers.iter() {
+                let j = matrix.answer_index(a).unwrap();
+                assert_eq!(matrix.get(i, j), Pattern::from(score(*g, *a)));
+            }
+        }
+        assert_eq!(matrix.answer_index(&guesses[0]), None);
+    }
+
+    #[test]
+    fn test_answer_sets() {
+        let answers: Vec<Word> = Dictionary::embedded().iter().copied().collect();
+        let guesses = words(&["raise", "geese", "fuzzy", "aback"]);
+        let matrix = PatternMatrix::new(guesses, answers);
+
+        for g in 0..matrix.guesses().len() {
+            let mut total = 0;
+            for p in Pattern::all() {
+                let expected: Vec<usize> = (0..matrix.answers().len())
+                    .filter(|a| matrix.get(g, *a) == p)
+                    .collect();
+                assert!(matrix.answers_with(g, p).eq(expected.iter().copied()));
+                let set = matrix.answer_set(g, p);
+                assert!(set.iter().eq(expected.iter().copied()));
+                assert_eq!(set.len(), expected.len());
+                total += set.len();
+            }
+            assert_eq!(total, matrix.answers().len());
+        }
+    }
+
+    #[test]
+    fn test_no_answers() {
+        let matrix = PatternMatrix::new(words(&["river", "abbey"]), vec![]);
+        for g in 0..matrix.guesses().len() {
+            assert!(matrix.row(g).is_empty());
+            assert_eq!(matrix.answers_with(g, Pattern::WIN).count(), 0);
+            assert!(matrix.answer_set(g, Pattern::WIN).is_empty());
+        }
+
+        let mut bytes = Vec::new();
+        matrix.write_to(&mut bytes).unwrap();
+        let loaded = PatternMatrix::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded, matrix);
+        assert_eq!(loaded.answers_with(1, Pattern::WIN).count(), 0);
+    }
+
+    #[test]
+    fn test_candidate_set() {
+        let mut set = CandidateSet::full(130);
+        assert_eq!(set.len(), 130);
+        assert!(set.iter().eq(0..130));
+        assert!(!set.contains(130));
+
+        set.remove(0);
+        set.remove(64);
+        set.retain(|i| i % 2 == 0);
+        assert!(set.iter().eq((2..130).step_by(2).filter(|i| *i != 64)));
+
+        let mut other = CandidateSet::empty(130);
+        assert!(other.is_empty());
+        other.insert(2);
+        other.insert(3);
+        other.insert(128);
+        set.intersect_with(&other);
+        assert!(set.iter().eq(vec![2, 128]));
+        assert!(!set.is_empty());
+
+        assert!(CandidateSet::full(128).iter().eq(0..128));
+        assert!(CandidateSet::full(0).is_empty());
+    }
+
+    #[test]
+    fn test_matrix_round_trip() {
+        let guesses = words(&["river", "abbey", "crave", "kings", "trees"]);
+        let answers = words(&["trees", "great", "abbey"]);
+        let matrix = PatternMatrix::new(guesses, answers);
+
+        let mut bytes = Vec::new();
+        matrix.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 8 + 4 + 4 + 5 * (5 + 3) + 5 * 3);
+        assert_eq!(PatternMatrix::read_from(bytes.as_slice()).unwrap(), matrix);
+
+        // Truncated data
+        let err = PatternMatrix::read_from(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // Corrupt pattern
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() = 243;
+        let err = PatternMatrix::read_from(corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Sizes too large to be real are rejected before anything is allocated
+        let mut huge = bytes.clone();
+        huge[8..16].copy_from_slice(&[0xff; 8]);
+        let err = PatternMatrix::read_from(huge.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // More answers than can be grouped
+        let mut many = bytes.clone();
+        many[12..16].copy_from_slice(&70000u32.to_le_bytes());
+        let err = PatternMatrix::read_from(many.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // Plausible sizes with the data missing fail once the data runs out
+        let mut short = bytes[..16].to_vec();
+        short[8..16].copy_from_slice(&[0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00]);
+        let err = PatternMatrix::read_from(short.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // Corrupt magic number
+        bytes[0] = b'W';
+        let err = PatternMatrix::read_from(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
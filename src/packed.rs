@@ -0,0 +1,131 @@
+use crate::{Letter, Word};
+use std::fmt;
+
+/// A `Word` packed into bits: the letter indices take 5 bits each, with the first
+/// letter most significant so packed words order the same way as words, and a
+/// 26 bit mask records which letters are present.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedWord {
+    letters: u32,
+    mask: u32,
+}
+
+impl PackedWord {
+    /// The lowest bit of each 5 bit letter
+    const LOW_BITS: u32 = 1 | 1 << 5 | 1 << 10 | 1 << 15 | 1 << 20;
+    /// The highest bit of each 5 bit letter
+    const HIGH_BITS: u32 = Self::LOW_BITS << 4;
+
+    pub fn new(word: Word) -> Self {
+        let mut letters = 0;
+        let mut mask = 0;
+        for l in word.iter() {
+            letters = (letters << 5) | u32::from(l.index());
+            mask |= 1 << l.index();
+        }
+        Self { letters, mask }
+    }
+
+    /// The letter at `position`, which must be less than 5.
+    pub fn get(&self, position: usize) -> Letter {
+        let index = (self.letters >> (5 * (4 - position))) & 0b11111;
+        Letter::LETTERS[index as usize]
+    }
+
+    /// The set of letters in the word, as a bit set indexed by `Letter::index`.
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    pub fn contains(&self, letter: &Letter) -> bool {
+        self.mask & (1 << letter.index()) != 0
+    }
+
+    pub fn count(&self, letter: &Letter) -> u8 {
+        if !self.contains(letter) {
+            return 0;
+        }
+        // Letters matching `letter` become zero, then set the high bit of each
+        // letter which is not zero and count the rest.
+        let x = self.letters ^ (u32::from(letter.index()) * Self::LOW_BITS);
+        let rest = !Self::HIGH_BITS & (Self::LOW_BITS * 0b11111);
+        let nonzero = (((x & rest) + rest) | x) & Self::HIGH_BITS;
+        5 - nonzero.count_ones() as u8
+    }
+
+    pub fn distinct_vowels(&self) -> u8 {
+        let vowels = Letter::VOWELS
+            .iter()
+            .fold(0u32, |acc, v| acc | (1 << v.index()));
+        (self.mask & vowels).count_ones() as u8
+    }
+}
+
+impl From<Word> for PackedWord {
+    fn from(word: Word) -> Self {
+        Self::new(word)
+    }
+}
+
+impl From<PackedWord> for Word {
+    fn from(packed: PackedWord) -> Self {
+        let mut letters = [Letter::default(); 5];
+        for (i, l) in letters.iter_mut().enumerate() {
+            *l = packed.get(i);
+        }
+        Word(letters)
+    }
+}
+
+impl fmt::Debug for PackedWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PackedWord")
+            .field(&Word::from(*self).to_string())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::packed::PackedWord;
+    use crate::{Letter, Word};
+
+    #[test]
+    fn test_packed_word() {
+        let mut words: Vec<Word> = Dictionary::embedded().iter().copied().collect();
+        words.sort_unstable();
+        words.extend(
+            ["geese", "mamma", "aaaaa", "zzzzz", "abcde"]
+                .iter()
+                .map(|s| Word::try_from_str(s).unwrap()),
+        );
+
+        for word in words.iter() {
+            let packed = PackedWord::from(*word);
+            assert_eq!(Word::from(packed), *word);
+            assert_eq!(packed.distinct_vowels(), word.distinct_vowels());
+            for (i, l) in word.iter().enumerate() {
+                assert_eq!(packed.get(i), *l);
+            }
+            for l in Letter::LETTERS.iter() {
+                assert_eq!(packed.contains(l), word.contains(l));
+                assert_eq!(packed.count(l), word.count(l));
+                assert_eq!(packed.mask() & (1 << l.index()) != 0, word.contains(l));
+            }
+        }
+
+        // Packed words order the same way as words
+        for pair in words.windows(2) {
+            assert_eq!(
+                PackedWord::from(pair[0]).cmp(&PackedWord::from(pair[1])),
+                pair[0].cmp(&pair[1]),
+            );
+        }
+
+        assert_eq!(
+            format!("{:?}", PackedWord::from(words[0])),
+            format!("PackedWord({:?})", words[0].to_string()),
+        );
+    }
+}
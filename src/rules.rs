@@ -0,0 +1,63 @@
+/// The rules a game is played under, shared by servers enforcing them and
+/// solvers playing by them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRules {
+    /// The most guesses allowed, or `None` for no limit
+    pub max_guesses: Option<usize>,
+    /// Whether every revealed hint must be used in later guesses
+    pub hard_mode: bool,
+    /// Whether a word may be guessed more than once
+    pub allow_repeats: bool,
+    /// Whether guesses must be words in the dictionary
+    pub check_dictionary: bool,
+}
+
+impl GameRules {
+    /// The rules of the original game: six guesses, not in hard mode.
+    pub const fn new() -> Self {
+        Self {
+            max_guesses: Some(6),
+            hard_mode: false,
+            allow_repeats: false,
+            check_dictionary: true,
+        }
+    }
+
+    /// How many more guesses may be made after `made`, or `None` for no limit.
+    pub fn guesses_left(&self, made: usize) -> Option<usize> {
+        self.max_guesses.map(|max| max.saturating_sub(made))
+    }
+
+    /// Whether another guess may be made after `made`.
+    pub fn can_guess(&self, made: usize) -> bool {
+        self.guesses_left(made) != Some(0)
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::GameRules;
+
+    #[test]
+    fn test_guesses_left() {
+        let rules = GameRules::default();
+        assert_eq!(rules.guesses_left(0), Some(6));
+        assert_eq!(rules.guesses_left(5), Some(1));
+        assert!(rules.can_guess(5));
+        assert!(!rules.can_guess(6));
+        assert_eq!(rules.guesses_left(7), Some(0));
+
+        let unlimited = GameRules {
+            max_guesses: None,
+            ..rules
+        };
+        assert_eq!(unlimited.guesses_left(100), None);
+        assert!(unlimited.can_guess(100));
+    }
+}
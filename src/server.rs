@@ -1,5 +1,11 @@
-use crate::{GuessOutcome, LetterOutcome, Word};
-use std::collections::HashSet;
+use crate::dictionary::Dictionary;
+use crate::rules::GameRules;
+use crate::strategy;
+use crate::{outcome_from_str, score, GuessOutcome, Letter, LetterOutcome, Pattern, Word};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
 use std::fmt;
 
 pub trait Server {
@@ -7,12 +13,29 @@ pub trait Server {
     fn submit(&mut self, guess: Word) -> Result<GuessOutcome, Error>;
 }
 
+/// A server which keeps track of the game being played.
+pub trait Game: Server {
+    fn status(&self) -> Status;
+
+    /// Every guess accepted so far with its outcome, in order.
+    fn history(&self) -> &[(Word, GuessOutcome)];
+
+    /// How many more guesses may be made, or `None` for no limit.
+    fn guesses_left(&self) -> Option<usize>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    InProgress,
+    /// The answer was found with this many guesses
+    Won(usize),
+    /// There are no guesses left, and this was the answer
+    Lost(Word),
+}
+
 pub struct InMemoryServer {
     answer: Word,
-    count_in_answer: [u8; 26],
-    guess_index: usize,
-    guesses: [Option<Word>; 6],
-    dictionary: HashSet<Word>,
+    referee: Referee,
 }
 
 impl fmt::Debug for InMemoryServer {
@@ -20,72 +43,494 @@ impl fmt::Debug for InMemoryServer {
         // Dictionary intentionally left off because it is never modified
         f.debug_struct("InMemoryServer")
             .field("answer", &self.answer)
-            .field("count_in_answer", &self.count_in_answer)
-            .field("guess_index", &self.guess_index)
-            .field("guesses", &self.guesses)
+            .field("history", &self.referee.history)
+            .field("rules", &self.referee.rules)
             .finish()
     }
 }
 
 impl InMemoryServer {
-    pub fn new(answer: Word, dictionary: HashSet<Word>) -> Self {
-        let mut count_in_answer = [0; 26];
-        for c in answer.iter() {
-            count_in_answer[c.index() as usize] += 1;
-        }
+    pub fn new<D: Into<Dictionary>>(answer: Word, dictionary: D) -> Self {
         Self {
             answer,
-            count_in_answer,
-            guess_index: 0,
-            guesses: [None; 6],
-            dictionary,
+            referee: Referee::new(dictionary.into()),
         }
     }
+
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.referee.rules = rules;
+        self
+    }
+
+    /// In hard mode every revealed hint must be used: correct letters stay in place
+    /// and letters known to be present must appear in every later guess.
+    pub fn with_hard_mode(mut self, hard_mode: bool) -> Self {
+        self.referee.rules.hard_mode = hard_mode;
+        self
+    }
 }
 
-impl Server for InMemoryServer {
-    fn can_guess(&self) -> bool {
-        self.guess_index < 6
+/// Enforces the rules of a game for a server, keeping track of the guesses made.
+#[derive(Debug, Clone)]
+struct Referee {
+    dictionary: Dictionary,
+    rules: GameRules,
+    history: Vec<(Word, GuessOutcome)>,
+    hints: Hints,
+}
+
+impl Referee {
+    fn new(dictionary: Dictionary) -> Self {
+        Self {
+            dictionary,
+            rules: GameRules::default(),
+            history: Vec::new(),
+            hints: Hints::new(),
+        }
     }
 
-    fn submit(&mut self, guess: Word) -> Result<GuessOutcome, Error> {
-        if !self.can_guess() {
+    fn is_won(&self) -> bool {
+        self.history
+            .last()
+            .map_or(false, |(_, o)| *o == [LetterOutcome::Correct; 5])
+    }
+
+    fn status(&self, answer: impl FnOnce() -> Word) -> Status {
+        if self.is_won() {
+            Status::Won(self.history.len())
+        } else if self.rules.can_guess(self.history.len()) {
+            Status::InProgress
+        } else {
+            Status::Lost(answer())
+        }
+    }
+
+    fn guesses_left(&self) -> Option<usize> {
+        if self.is_won() {
+            Some(0)
+        } else {
+            self.rules.guesses_left(self.history.len())
+        }
+    }
+
+    /// Check that `guess` may be played next.
+    fn check(&self, guess: &Word) -> Result<(), Error> {
+        if self.guesses_left() == Some(0) {
             return Err(Error::GameOver);
         }
-        if self.guesses[..self.guess_index].contains(&Some(guess)) {
+        if !self.rules.allow_repeats && self.history.iter().any(|(g, _)| g == guess) {
             return Err(Error::AlreadyGuessed);
         }
-        if !self.dictionary.contains(&guess) {
+        if self.rules.check_dictionary && !self.dictionary.is_guess(guess) {
             return Err(Error::InvalidWord);
         }
-        self.guesses[self.guess_index] = Some(guess);
-        self.guess_index += 1;
-
-        let mut result = GuessOutcome::default();
-        let mut correct_count_in_guess = [0u8; 26];
-        // In the first pass, find all the correct letters
-        for (i, (x, y)) in guess.iter().zip(self.answer.iter()).enumerate() {
-            if x == y {
-                result[i] = LetterOutcome::Correct;
-                correct_count_in_guess[x.index() as usize] += 1;
+        if self.rules.hard_mode {
+            if let Some(hint) = self.hints.ignored_by(guess) {
+                return Err(Error::HintIgnored(hint));
             }
         }
-        // In the second pass, set present or absent only based
-        // on the non-correct positions
-        for (i, x) in guess.into_iter().enumerate() {
-            if result[i] == LetterOutcome::Correct {
-                continue;
+        Ok(())
+    }
+
+    fn record(&mut self, guess: Word, outcome: GuessOutcome) {
+        self.history.push((guess, outcome));
+        self.hints.update(&guess, &outcome);
+    }
+}
+
+/// The hints revealed by the guesses so far, which hard mode requires later guesses to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hints {
+    /// Letters revealed at each position
+    correct: [Option<Letter>; 5],
+    /// How many of each letter are known to be in the answer, by `Letter::index`
+    present: [u8; 26],
+}
+
+impl Hints {
+    pub fn new() -> Self {
+        Self {
+            correct: [None; 5],
+            present: [0; 26],
+        }
+    }
+
+    /// The first hint which `guess` does not use, if any.
+    pub fn ignored_by(&self, guess: &Word) -> Option<Hint> {
+        for (position, (correct, letter)) in self.correct.iter().zip(guess.iter()).enumerate() {
+            if let Some(c) = *correct {
+                if c != *letter {
+                    return Some(Hint::Correct {
+                        position,
+                        letter: c,
+                    });
+                }
+            }
+        }
+        for letter in Letter::LETTERS.iter() {
+            let count = self.present[letter.index() as usize];
+            if guess.count(letter) < count {
+                return Some(Hint::Present {
+                    letter: *letter,
+                    count,
+                });
+            }
+        }
+        None
+    }
+
+    pub fn update(&mut self, guess: &Word, outcome: &GuessOutcome) {
+        let mut found = [0u8; 26];
+        for (i, (letter, o)) in guess.iter().zip(outcome.iter()).enumerate() {
+            match o {
+                LetterOutcome::Correct => {
+                    self.correct[i] = Some(*letter);
+                    found[letter.index() as usize] += 1;
+                }
+                LetterOutcome::Present => found[letter.index() as usize] += 1,
+                LetterOutcome::Absent => (),
             }
-            let j = x.index() as usize;
-            if self.count_in_answer[j] - correct_count_in_guess[j] == 0 {
-                result[i] = LetterOutcome::Absent;
-            } else {
-                result[i] = LetterOutcome::Present;
-                correct_count_in_guess[j] += 1;
+        }
+        for (p, f) in self.present.iter_mut().zip(found) {
+            *p = (*p).max(f);
+        }
+    }
+}
+
+impl Default for Hints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server for InMemoryServer {
+    fn can_guess(&self) -> bool {
+        self.referee.guesses_left() != Some(0)
+    }
+
+    fn submit(&mut self, guess: Word) -> Result<GuessOutcome, Error> {
+        self.referee.check(&guess)?;
+        let outcome = score(guess, self.answer);
+        self.referee.record(guess, outcome);
+        Ok(outcome)
+    }
+}
+
+impl Game for InMemoryServer {
+    fn status(&self) -> Status {
+        self.referee.status(|| self.answer)
+    }
+
+    fn history(&self) -> &[(Word, GuessOutcome)] {
+        &self.referee.history
+    }
+
+    fn guesses_left(&self) -> Option<usize> {
+        self.referee.guesses_left()
+    }
+}
+
+/// A server which never commits to an answer, like Absurdle. Each guess gets the
+/// outcome shared by the most answers still consistent with every outcome so far,
+/// only conceding a win when no other outcome is left.
+pub struct AdversarialServer {
+    /// Answers consistent with every outcome given so far, sorted
+    candidates: Vec<Word>,
+    referee: Referee,
+}
+
+impl fmt::Debug for AdversarialServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdversarialServer")
+            .field("candidates", &self.candidates.len())
+            .field("history", &self.referee.history)
+            .field("rules", &self.referee.rules)
+            .finish()
+    }
+}
+
+impl AdversarialServer {
+    pub fn new<D: Into<Dictionary>>(dictionary: D) -> Self {
+        let dictionary = dictionary.into();
+        let mut candidates: Vec<Word> = dictionary.answers().iter().copied().collect();
+        candidates.sort_unstable();
+        assert!(!candidates.is_empty(), "There must be at least one answer");
+        Self {
+            candidates,
+            referee: Referee::new(dictionary),
+        }
+    }
+
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.referee.rules = rules;
+        self
+    }
+
+    pub fn with_hard_mode(mut self, hard_mode: bool) -> Self {
+        self.referee.rules.hard_mode = hard_mode;
+        self
+    }
+
+    /// Answers which are consistent with every outcome given so far.
+    pub fn candidates(&self) -> &[Word] {
+        &self.candidates
+    }
+}
+
+impl Server for AdversarialServer {
+    fn can_guess(&self) -> bool {
+        self.referee.guesses_left() != Some(0)
+    }
+
+    fn submit(&mut self, guess: Word) -> Result<GuessOutcome, Error> {
+        self.referee.check(&guess)?;
+
+        // Patterns are tried in order, so ties go to the one revealing least. The
+        // winning pattern comes last, so it is only chosen when it is the only one.
+        let buckets = strategy::buckets(&guess, &self.candidates);
+        let mut best: Option<Pattern> = None;
+        for p in Pattern::all() {
+            if buckets[p.index()] > best.map_or(0, |b| buckets[b.index()]) {
+                best = Some(p);
             }
         }
+        // There is always at least one candidate left
+        let outcome = best.unwrap_or(Pattern::WIN).outcome();
+        self.candidates.retain(|a| score(guess, *a) == outcome);
+        self.referee.record(guess, outcome);
+        Ok(outcome)
+    }
+}
+
+impl Game for AdversarialServer {
+    /// When the game is lost, the first remaining candidate is revealed as the answer.
+    fn status(&self) -> Status {
+        self.referee.status(|| self.candidates[0])
+    }
 
-        Ok(result)
+    fn history(&self) -> &[(Word, GuessOutcome)] {
+        &self.referee.history
+    }
+
+    fn guesses_left(&self) -> Option<usize> {
+        self.referee.guesses_left()
+    }
+}
+
+/// A server scoring each guess against several hidden answers at once, one per
+/// board, as in Dordle, Quordle and Octordle.
+pub trait MultiServer {
+    /// The number of boards being played.
+    fn boards(&self) -> usize;
+
+    fn is_solved(&self, board: usize) -> bool;
+
+    fn can_guess(&self) -> bool;
+
+    /// Score `guess` against the answer of every board, with `None` for the boards
+    /// which were already solved.
+    fn submit(&mut self, guess: Word) -> Result<Vec<Option<GuessOutcome>>, Error>;
+}
+
+#[derive(Debug)]
+pub struct MultiBoardServer {
+    boards: Vec<InMemoryServer>,
+    /// The most guesses allowed over all the boards, or `None` for no limit
+    max_guesses: Option<usize>,
+    guess_count: usize,
+}
+
+impl MultiBoardServer {
+    /// Create a server with a board for each answer, allowing five more guesses
+    /// than there are boards.
+    pub fn new<D: Into<Dictionary>>(answers: &[Word], dictionary: D) -> Self {
+        let dictionary = dictionary.into();
+        let server = Self {
+            boards: answers
+                .iter()
+                .map(|a| InMemoryServer::new(*a, dictionary.clone()))
+                .collect(),
+            max_guesses: None,
+            guess_count: 0,
+        };
+        server.with_rules(GameRules {
+            max_guesses: Some(answers.len() + 5),
+            ..GameRules::default()
+        })
+    }
+
+    /// Play every board by `rules`, where the guess limit applies to the game as a whole.
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.max_guesses = rules.max_guesses;
+        let board_rules = GameRules {
+            max_guesses: None,
+            ..rules
+        };
+        for board in self.boards.iter_mut() {
+            board.referee.rules = board_rules;
+        }
+        self
+    }
+
+    /// The number of guesses the board was solved in, if it has been.
+    pub fn solved_in(&self, board: usize) -> Option<usize> {
+        match self.boards[board].status() {
+            Status::Won(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// How many more guesses may be made, or `None` for no limit.
+    pub fn guesses_left(&self) -> Option<usize> {
+        if (0..self.boards.len()).all(|b| self.is_solved(b)) {
+            return Some(0);
+        }
+        self.max_guesses
+            .map(|max| max.saturating_sub(self.guess_count))
+    }
+}
+
+impl MultiServer for MultiBoardServer {
+    fn boards(&self) -> usize {
+        self.boards.len()
+    }
+
+    fn is_solved(&self, board: usize) -> bool {
+        self.solved_in(board).is_some()
+    }
+
+    fn can_guess(&self) -> bool {
+        self.guesses_left() != Some(0)
+    }
+
+    fn submit(&mut self, guess: Word) -> Result<Vec<Option<GuessOutcome>>, Error> {
+        if !self.can_guess() {
+            return Err(Error::GameOver);
+        }
+        // Check every board before scoring any, so a rejected guess changes nothing
+        for board in self.boards.iter().filter(|b| b.can_guess()) {
+            board.referee.check(&guess)?;
+        }
+        self.guess_count += 1;
+        self.boards
+            .iter_mut()
+            .map(|board| {
+                if board.can_guess() {
+                    board.submit(guess).map(Some)
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect()
+    }
+}
+
+/// A server which lies about the outcome of some letters of every guess, like Fibble.
+/// The lies are chosen at random, but a correct guess is always reported as a win and
+/// nothing else ever is. In hard mode, the hints which must be used are those of the
+/// reported outcomes, lies included, since the true ones are never revealed; a lie can
+/// then leave a hint which the answer itself does not use, and the game unwinnable.
+pub struct LyingServer {
+    answer: Word,
+    /// Referees the outcomes as reported
+    referee: Referee,
+    /// The number of letters to lie about in each outcome
+    lies: usize,
+    rng: StdRng,
+}
+
+impl fmt::Debug for LyingServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LyingServer")
+            .field("answer", &self.answer)
+            .field("lies", &self.lies)
+            .field("history", &self.referee.history)
+            .field("rules", &self.referee.rules)
+            .finish()
+    }
+}
+
+impl LyingServer {
+    /// Create a server lying about one letter of each outcome, choosing which
+    /// with a random number generator seeded by `seed`.
+    pub fn new<D: Into<Dictionary>>(answer: Word, dictionary: D, seed: u64) -> Self {
+        Self {
+            answer,
+            referee: Referee::new(dictionary.into()),
+            lies: 1,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Lie about `lies` letters of each outcome, which must be at most 5.
+    pub fn with_lies(mut self, lies: usize) -> Self {
+        assert!(lies <= 5, "There are only five letters to lie about");
+        self.lies = lies;
+        self
+    }
+
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.referee.rules = rules;
+        self
+    }
+
+    pub fn with_hard_mode(mut self, hard_mode: bool) -> Self {
+        self.referee.rules.hard_mode = hard_mode;
+        self
+    }
+
+    /// Change the outcome of `lies` letters, without making it look like a win.
+    fn lie(&mut self, mut outcome: GuessOutcome) -> GuessOutcome {
+        for i in index::sample(&mut self.rng, 5, self.lies) {
+            let truth = outcome[i];
+            // Making the only letter which is not correct correct would look like a win
+            let would_win = outcome
+                .iter()
+                .enumerate()
+                .all(|(j, o)| j == i || *o == LetterOutcome::Correct);
+            let others: Vec<LetterOutcome> = [
+                LetterOutcome::Correct,
+                LetterOutcome::Present,
+                LetterOutcome::Absent,
+            ]
+            .iter()
+            .copied()
+            .filter(|o| *o != truth && !(would_win && *o == LetterOutcome::Correct))
+            .collect();
+            outcome[i] = others[self.rng.gen_range(0..others.len())];
+        }
+        outcome
+    }
+}
+
+impl Server for LyingServer {
+    fn can_guess(&self) -> bool {
+        self.referee.guesses_left() != Some(0)
+    }
+
+    fn submit(&mut self, guess: Word) -> Result<GuessOutcome, Error> {
+        self.referee.check(&guess)?;
+        let mut outcome = score(guess, self.answer);
+        if outcome != [LetterOutcome::Correct; 5] {
+            outcome = self.lie(outcome);
+        }
+        self.referee.record(guess, outcome);
+        Ok(outcome)
+    }
+}
+
+impl Game for LyingServer {
+    fn status(&self) -> Status {
+        self.referee.status(|| self.answer)
+    }
+
+    /// The guesses with the outcomes reported for them, lies included.
+    fn history(&self) -> &[(Word, GuessOutcome)] {
+        &self.referee.history
+    }
+
+    fn guesses_left(&self) -> Option<usize> {
+        self.referee.guesses_left()
     }
 }
 
@@ -100,47 +545,27 @@ impl Server for InteractiveServer {
         println!("Guess: {:?}", guess);
 
         let mut input = String::with_capacity(5);
-        let mut outcome = [LetterOutcome::Absent; 5];
         loop {
             input.clear();
-            if let Err(_) = std::io::stdin().read_line(&mut input) {
+            if std::io::stdin().read_line(&mut input).is_err() {
                 println!("Some error occurred, try again.");
             }
             let trimmed = input.trim();
-
-            let mut parse_err = false;
-            for (i, b) in trimmed.bytes().enumerate() {
-                if i == 5 {
-                    break;
-                }
-                match b {
-                    b'*' => outcome[i] = LetterOutcome::Correct,
-                    b'+' => outcome[i] = LetterOutcome::Present,
-                    b'-' => outcome[i] = LetterOutcome::Absent,
-                    b'!' => return Err(Error::GameOver),
-                    _ => {
-                        println!(
-                            "Unrecognized character, use only *=correct +=present -=absent !=game_over"
-                        );
-                        parse_err = true;
-                        break;
-                    }
-                }
+            if let Some(outcome) = outcome_from_str(trimmed) {
+                return Ok(outcome);
             }
-
-            if trimmed.len() < 5 {
-                println!("Input too short, try again.");
-                parse_err = true;
-            } else if trimmed.len() > 5 {
-                println!("Input too long, try again.");
-                parse_err = true;
+            if trimmed.starts_with('!') {
+                return Err(Error::GameOver);
             }
 
-            if !parse_err {
-                break;
+            match trimmed.len().cmp(&5) {
+                Ordering::Less => println!("Input too short, try again."),
+                Ordering::Greater => println!("Input too long, try again."),
+                Ordering::Equal => println!(
+                    "Unrecognized character, use only *=correct +=present -=absent !=game_over"
+                ),
             }
         }
-        Ok(outcome)
     }
 }
 
@@ -149,19 +574,41 @@ pub enum Error {
     GameOver,
     AlreadyGuessed,
     InvalidWord,
+    /// In hard mode, the guess did not use a hint revealed by an earlier guess
+    HintIgnored(Hint),
+}
+
+/// Something revealed about the answer which hard mode requires later guesses to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// The letter is at this position
+    Correct { position: usize, letter: Letter },
+    /// The letter appears at least `count` times
+    Present { letter: Letter, count: u8 },
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::util::{word, words};
     use crate::{
-        server::{self, InMemoryServer, Server},
-        GuessOutcome, LetterOutcome, Word,
+        dictionary::Dictionary,
+        outcome_to_str,
+        rules::GameRules,
+        server::{
+            self, AdversarialServer, Game, Hint, InMemoryServer, LyingServer, MultiBoardServer,
+            MultiServer, Server, Status,
+        },
+        solver::Solver,
+        strategy::{Minimax, Strategy},
+        Letter, LetterOutcome, Word,
     };
+    use std::collections::HashSet;
 
     #[test]
     fn test_guess_submit() {
         let word = Word::try_from_str("trees").unwrap();
-        let dictionary = vec!["river", "abbey", "crave", "kings", "great", "trees"]
+        // A plain set of words still serves as the dictionary
+        let dictionary: HashSet<Word> = vec!["river", "abbey", "crave", "kings", "great", "trees"]
             .into_iter()
             .map(|s| Word::try_from_str(s).unwrap())
             .collect();
@@ -255,11 +702,8 @@ mod tests {
 
     #[test]
     fn test_duplicate_letters_in_guess() {
-        fn to_str(xs: &[u8]) -> &str {
-            std::str::from_utf8(xs).unwrap()
-        }
         let word = Word::try_from_str("whack").unwrap();
-        let dictionary = vec!["whack", "audio", "snake", "track", "clack"]
+        let dictionary: Dictionary = vec!["whack", "audio", "snake", "track", "clack"]
             .into_iter()
             .map(|s| Word::try_from_str(s).unwrap())
             .collect();
@@ -267,29 +711,29 @@ mod tests {
 
         let guess = Word::try_from_str("audio").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "+----",);
+        assert_eq!(outcome_to_str(&outcome), "+----",);
 
         let guess = Word::try_from_str("snake").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "--*+-",);
+        assert_eq!(outcome_to_str(&outcome), "--*+-",);
 
         let guess = Word::try_from_str("track").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "--***",);
+        assert_eq!(outcome_to_str(&outcome), "--***",);
 
         // Note the first 'c' is considered absent because the second
         // 'c' is already in the correct position and there is only one
         // 'c' in the word.
         let guess = Word::try_from_str("clack").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "--***",);
+        assert_eq!(outcome_to_str(&outcome), "--***",);
 
         let guess = Word::try_from_str("whack").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "*****",);
+        assert_eq!(outcome_to_str(&outcome), "*****",);
 
         let word = Word::try_from_str("whack").unwrap();
-        let dictionary = vec!["whack", "cacao"]
+        let dictionary: Dictionary = vec!["whack", "cacao"]
             .into_iter()
             .map(|s| Word::try_from_str(s).unwrap())
             .collect();
@@ -300,16 +744,13 @@ mod tests {
         // Similarly for the 'a's.
         let guess = Word::try_from_str("cacao").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "++---",);
+        assert_eq!(outcome_to_str(&outcome), "++---",);
     }
 
     #[test]
     fn test_duplicate_letters_in_answer() {
-        fn to_str(xs: &[u8]) -> &str {
-            std::str::from_utf8(xs).unwrap()
-        }
         let word = Word::try_from_str("dwell").unwrap();
-        let dictionary = vec!["dwell", "audio", "dense", "dryer"]
+        let dictionary: Dictionary = vec!["dwell", "audio", "dense", "dryer"]
             .into_iter()
             .map(|s| Word::try_from_str(s).unwrap())
             .collect();
@@ -317,26 +758,266 @@ mod tests {
 
         let guess = Word::try_from_str("audio").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "--+--",);
+        assert_eq!(outcome_to_str(&outcome), "--+--",);
 
         let guess = Word::try_from_str("dense").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "*+---",);
+        assert_eq!(outcome_to_str(&outcome), "*+---",);
 
         let guess = Word::try_from_str("dryer").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "*--+-",);
+        assert_eq!(outcome_to_str(&outcome), "*--+-",);
 
         let guess = Word::try_from_str("dwell").unwrap();
         let outcome = server.submit(guess).unwrap();
-        assert_eq!(to_str(&guess_outcome_to_ascii(outcome)), "*****",);
+        assert_eq!(outcome_to_str(&outcome), "*****",);
     }
 
-    fn guess_outcome_to_ascii(g: GuessOutcome) -> [u8; 5] {
-        crate::util::map_array(g, |l| match l {
-            LetterOutcome::Absent => b'-',
-            LetterOutcome::Present => b'+',
-            LetterOutcome::Correct => b'*',
-        })
+    #[test]
+    fn test_probe_words() {
+        let dictionary = Dictionary::new(words(&["trees", "river"]), words(&["abbey"]));
+        let mut server = InMemoryServer::new(Word::try_from_str("trees").unwrap(), dictionary);
+
+        // Words which cannot be the answer are accepted as guesses
+        let guess = Word::try_from_str("abbey").unwrap();
+        assert!(server.submit(guess).is_ok());
+
+        let guess = Word::try_from_str("crave").unwrap();
+        assert_eq!(server.submit(guess), Err(server::Error::InvalidWord));
+    }
+
+    #[test]
+    fn test_hard_mode() {
+        let dictionary: Dictionary = words(&["geese", "sheep", "eerie", "sleep", "wheel"])
+            .into_iter()
+            .collect();
+        let letter = |c| Letter::new(c).unwrap();
+        let answer = word("sheep");
+
+        let mut server = InMemoryServer::new(answer, dictionary.clone()).with_hard_mode(true);
+        // geese has its middle e in place, and another e and an s in the wrong places
+        assert!(server.submit(word("geese")).is_ok());
+        assert_eq!(
+            server.submit(word("eerie")),
+            Err(server::Error::HintIgnored(Hint::Correct {
+                position: 2,
+                letter: letter(b'e'),
+            }))
+        );
+        assert_eq!(
+            server.submit(word("wheel")),
+            Err(server::Error::HintIgnored(Hint::Present {
+                letter: letter(b's'),
+                count: 1,
+            }))
+        );
+        assert!(server.submit(word("sleep")).is_ok());
+        assert_eq!(
+            server.submit(word("sheep")),
+            Ok([LetterOutcome::Correct; 5])
+        );
+
+        // Hints are ignored outside hard mode
+        let mut server = InMemoryServer::new(answer, dictionary);
+        assert!(server.submit(word("geese")).is_ok());
+        assert!(server.submit(word("eerie")).is_ok());
+    }
+
+    #[test]
+    fn test_game_rules() {
+        let dictionary: Dictionary = words(&["river", "abbey", "crave", "kings", "great", "trees"])
+            .into_iter()
+            .collect();
+        let rules = GameRules {
+            max_guesses: None,
+            hard_mode: false,
+            allow_repeats: true,
+            check_dictionary: false,
+        };
+        let mut server = InMemoryServer::new(word("trees"), dictionary.clone()).with_rules(rules);
+        for _ in 0..10 {
+            assert!(server.can_guess());
+            assert!(server.submit(word("river")).is_ok());
+        }
+        assert!(server.submit(word("ghwsd")).is_ok());
+
+        let rules = GameRules {
+            max_guesses: Some(2),
+            ..GameRules::default()
+        };
+        let mut server = InMemoryServer::new(word("trees"), dictionary).with_rules(rules);
+        assert!(server.submit(word("river")).is_ok());
+        assert!(server.submit(word("abbey")).is_ok());
+        assert!(!server.can_guess());
+        assert_eq!(server.submit(word("crave")), Err(server::Error::GameOver));
+    }
+
+    #[test]
+    fn test_status_history() {
+        let dictionary: Dictionary = words(&["river", "abbey", "crave", "kings", "great", "trees"])
+            .into_iter()
+            .collect();
+
+        let mut server = InMemoryServer::new(word("trees"), dictionary.clone());
+        assert_eq!(server.status(), Status::InProgress);
+        assert_eq!(server.guesses_left(), Some(6));
+        let outcome = server.submit(word("river")).unwrap();
+        assert!(server.submit(word("ghwsd")).is_err());
+        assert_eq!(server.history(), &[(word("river"), outcome)]);
+        assert_eq!(server.guesses_left(), Some(5));
+        server.submit(word("trees")).unwrap();
+        assert_eq!(server.status(), Status::Won(2));
+        assert_eq!(server.guesses_left(), Some(0));
+        assert!(!server.can_guess());
+
+        let rules = GameRules {
+            max_guesses: Some(2),
+            ..GameRules::default()
+        };
+        let mut server = InMemoryServer::new(word("trees"), dictionary).with_rules(rules);
+        server.submit(word("river")).unwrap();
+        assert_eq!(server.status(), Status::InProgress);
+        server.submit(word("abbey")).unwrap();
+        assert_eq!(server.status(), Status::Lost(word("trees")));
+        assert_eq!(server.history().len(), 2);
+    }
+
+    #[test]
+    fn test_adversarial_server() {
+        let dictionary: Dictionary = words(&["batch", "catch"]).into_iter().collect();
+
+        // Any guess which can be dodged is
+        let mut server = AdversarialServer::new(dictionary);
+        let outcome = server.submit(word("batch")).unwrap();
+        assert_eq!(outcome, crate::score(word("batch"), word("catch")));
+        assert_eq!(server.candidates(), &[word("catch")]);
+        assert_eq!(server.status(), Status::InProgress);
+        assert_eq!(
+            server.submit(word("catch")),
+            Ok([LetterOutcome::Correct; 5])
+        );
+        assert_eq!(server.status(), Status::Won(2));
+
+        // Solvers still win against the full dictionary, only more slowly
+        let dictionary = Dictionary::embedded();
+        let status = play_adversary(Solver::new(dictionary.clone()), &dictionary);
+        assert!(matches!(status, Status::Won(n) if n <= 6), "{:?}", status);
+        let status = play_adversary(
+            Solver::with_strategy(dictionary.clone(), Minimax),
+            &dictionary,
+        );
+        assert!(matches!(status, Status::Won(n) if n <= 6), "{:?}", status);
+    }
+
+    #[test]
+    fn test_multi_board_server() {
+        let dictionary: Dictionary = words(&["river", "abbey", "crave", "kings", "great", "trees"])
+            .into_iter()
+            .collect();
+        let answers = [word("trees"), word("great")];
+
+        let mut server = MultiBoardServer::new(&answers, dictionary.clone());
+        assert_eq!(server.boards(), 2);
+        assert_eq!(server.guesses_left(), Some(7));
+        assert_eq!(
+            server.submit(word("river")),
+            Ok(vec![
+                Some(crate::score(word("river"), answers[0])),
+                Some(crate::score(word("river"), answers[1])),
+            ])
+        );
+        assert_eq!(
+            server.submit(word("ghwsd")),
+            Err(server::Error::InvalidWord)
+        );
+        assert_eq!(
+            server.submit(word("river")),
+            Err(server::Error::AlreadyGuessed)
+        );
+        assert_eq!(server.guesses_left(), Some(6));
+
+        let outcomes = server.submit(word("great")).unwrap();
+        assert_eq!(outcomes[1], Some([LetterOutcome::Correct; 5]));
+        assert!(server.is_solved(1));
+        assert_eq!(server.solved_in(1), Some(2));
+        assert!(!server.is_solved(0));
+
+        // Solved boards are no longer scored
+        let outcomes = server.submit(word("trees")).unwrap();
+        assert_eq!(outcomes, vec![Some([LetterOutcome::Correct; 5]), None]);
+        assert!(!server.can_guess());
+        assert_eq!(server.submit(word("kings")), Err(server::Error::GameOver));
+
+        // The guess limit applies to the game as a whole
+        let rules = GameRules {
+            max_guesses: Some(1),
+            ..GameRules::default()
+        };
+        let mut server = MultiBoardServer::new(&answers, dictionary).with_rules(rules);
+        assert!(server.submit(word("kings")).is_ok());
+        assert!(!server.can_guess());
+    }
+
+    #[test]
+    fn test_lying_server() {
+        let dictionary = Dictionary::embedded();
+        let answer = Word::try_from_str("sheep").unwrap();
+        let guesses = words(&["geese", "sheen", "speed", "shelf", "sweep", "sheer"]);
+        let unlimited = GameRules {
+            max_guesses: None,
+            ..GameRules::default()
+        };
+
+        for lies in 0..=5 {
+            let mut server = LyingServer::new(answer, dictionary.clone(), lies as u64)
+                .with_lies(lies)
+                .with_rules(unlimited);
+            for guess in guesses.iter() {
+                let truth = crate::score(*guess, answer);
+                let outcome = server.submit(*guess).unwrap();
+                let differences = truth.iter().zip(outcome.iter()).filter(|(t, o)| t != o);
+                assert_eq!(differences.count(), lies);
+                assert_ne!(outcome, [LetterOutcome::Correct; 5]);
+            }
+            assert_eq!(server.submit(answer), Ok([LetterOutcome::Correct; 5]));
+            assert_eq!(server.status(), Status::Won(guesses.len() + 1));
+            assert_eq!(server.history().len(), guesses.len() + 1);
+        }
+
+        // The same seed tells the same lies
+        let play = |seed| {
+            let mut server = LyingServer::new(answer, dictionary.clone(), seed);
+            guesses
+                .iter()
+                .map(|g| server.submit(*g).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(play(7), play(7));
+
+        // Hard mode holds guesses to the hints of the outcomes reported, not the true ones
+        let mut server = LyingServer::new(answer, dictionary, 2).with_hard_mode(true);
+        let outcome = server.submit(word("shore")).unwrap();
+        assert_eq!(outcome_to_str(&outcome), "-*--+");
+        assert_eq!(
+            server.submit(word("sleep")),
+            Err(server::Error::HintIgnored(Hint::Correct {
+                position: 1,
+                letter: Letter::new(b'h').unwrap(),
+            }))
+        );
+        assert!(server.submit(word("wheel")).is_ok());
+    }
+
+    fn play_adversary<T: Strategy>(mut solver: Solver<T>, dictionary: &Dictionary) -> Status {
+        let mut server = AdversarialServer::new(dictionary.clone());
+        while server.can_guess() {
+            let (guess, outcome) = solver.guess(&mut server).unwrap();
+            // The adversary only ever gives outcomes consistent with some answer
+            assert!(server
+                .candidates()
+                .iter()
+                .all(|a| crate::score(guess, *a) == outcome));
+        }
+        server.status()
     }
 }
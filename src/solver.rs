@@ -1,204 +1,819 @@
-use crate::server::{self, Server};
-use crate::{util, GuessOutcome, Letter, LetterOutcome, Word};
-use std::collections::HashSet;
-
-#[derive(Debug)]
-pub struct Solver {
-    guess_index: usize,
-    guess_outcomes: [Option<GuessOutcome>; 6],
-    letters_state: [LetterState; 26],
-    dictionary: Vec<Word>,
+use crate::dictionary::{self, Dictionary};
+use crate::matrix::{CandidateSet, PatternMatrix};
+use crate::rules::GameRules;
+use crate::server::{self, Hints, MultiServer, Server};
+use crate::strategy::{self, Context, Entropy, Strategy};
+use crate::{score, GuessOutcome, Letter, LetterOutcome, Pattern, Word};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Solver<T = Entropy> {
+    knowledge: Knowledge,
+    matrix: Arc<PatternMatrix>,
+    /// Indices of the matrix answers which could still be the answer
+    candidates: CandidateSet,
+    /// Prior likelihood of each matrix answer, by index
+    weights: Arc<[f64]>,
+    /// Indices of the matrix guesses which the solver may still play
+    guesses: Vec<usize>,
+    strategy: T,
+    filter: Filter,
+    rules: GameRules,
+    next_guess: Option<Word>,
 }
 
-impl Solver {
-    pub fn new(dict: HashSet<Word>) -> Self {
-        // sort words by number of distinct vowels for better picking
-        let mut dictionary: Vec<Word> = dict.into_iter().collect();
-        dictionary.sort_unstable_by_key(|w| w.distinct_vowels());
+/// How the solver decides which words could still be the answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// Keep words satisfying the letter constraints learned so far
+    Constraints,
+    /// Keep words which would have produced exactly the observed outcome for every guess,
+    /// by intersecting with the precomputed answers for each guess and pattern
+    Consistency,
+    /// Keep words whose outcome for every guess differs from the observed one in at most
+    /// this many letters, for servers which lie about some letters of each outcome
+    Lies(usize),
+}
+
+impl Filter {
+    /// Remove the candidates ruled out by the latest guess, which must already
+    /// be recorded in `knowledge`.
+    fn apply(
+        self,
+        candidates: &mut CandidateSet,
+        matrix: &PatternMatrix,
+        knowledge: &Knowledge,
+        (guess, outcome): (Word, GuessOutcome),
+    ) {
+        if let Some(i) = matrix.answer_index(&guess) {
+            if outcome != [LetterOutcome::Correct; 5] {
+                candidates.remove(i);
+            }
+        }
+        let answers = matrix.answers();
+        match self {
+            Filter::Constraints => {
+                let constraints = &knowledge.constraints;
+                candidates.retain(|a| constraints.satisfies(&answers[a]));
+            }
+            // Earlier guesses have already been checked, only the latest one is needed
+            Filter::Consistency => match matrix.guess_index(&guess) {
+                Some(i) => candidates.intersect_with(&matrix.answer_set(i, Pattern::from(outcome))),
+                None => candidates.retain(|a| score(guess, answers[a]) == outcome),
+            },
+            Filter::Lies(lies) => match matrix.guess_index(&guess) {
+                Some(i) => {
+                    let row = matrix.row(i);
+                    candidates.retain(|a| differences(&row[a].outcome(), &outcome) <= lies)
+                }
+                None => {
+                    candidates.retain(|a| differences(&score(guess, answers[a]), &outcome) <= lies)
+                }
+            },
+        }
+    }
+
+    /// Whether outcomes are taken to be true, so that constraints can be learned
+    /// from them.
+    pub fn trusts_outcomes(self) -> bool {
+        !matches!(self, Filter::Lies(lies) if lies > 0)
+    }
+}
+
+/// The number of letters with different outcomes.
+fn differences(a: &GuessOutcome, b: &GuessOutcome) -> usize {
+    a.iter().zip(b.iter()).filter(|(x, y)| x != y).count()
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::Consistency
+    }
+}
+
+/// Everything the solver has learned from the outcomes of its guesses so far.
+#[derive(Debug, Clone)]
+pub struct Knowledge {
+    guess_outcomes: Vec<(Word, GuessOutcome)>,
+    constraints: Constraints,
+    hints: Hints,
+}
+
+impl Knowledge {
+    fn new() -> Self {
         Self {
-            guess_index: 0,
-            guess_outcomes: [None; 6],
-            letters_state: [LetterState::Unknown; 26],
-            dictionary,
+            guess_outcomes: Vec::new(),
+            constraints: Constraints::new(),
+            hints: Hints::new(),
         }
     }
 
-    pub fn guess<S: Server>(&mut self, server: &mut S) -> Result<(Word, GuessOutcome), Error> {
-        // Select a random word still in the dictionary
-        let guess = self.dictionary.pop().ok_or(Error::Stumped)?;
+    /// Record the outcome of a guess, learning constraints from it only if it is
+    /// `trusted` to be true. Hints are learned either way, as hard mode enforces the
+    /// hints of the outcomes reported whether or not they are true.
+    fn record(&mut self, guess: Word, outcome: GuessOutcome, trusted: bool) {
+        self.guess_outcomes.push((guess, outcome));
+        self.hints.update(&guess, &outcome);
+        if trusted {
+            self.constraints.update(guess, outcome);
+        }
+    }
+
+    /// The constraints on the answer, learned only from outcomes which are trusted.
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    /// The hints which must be used by later guesses in hard mode.
+    pub fn hints(&self) -> &Hints {
+        &self.hints
+    }
+
+    pub fn outcomes(&self) -> impl Iterator<Item = &(Word, GuessOutcome)> {
+        self.guess_outcomes.iter()
+    }
+
+    /// The number of guesses made so far.
+    pub fn guess_count(&self) -> usize {
+        self.guess_outcomes.len()
+    }
+
+    /// Whether `word` would have produced the observed outcome for every guess if
+    /// it were the answer. This is the definition of which words could be the answer.
+    pub fn consistent_with(&self, word: &Word) -> bool {
+        self.outcomes()
+            .all(|(guess, outcome)| score(*guess, *word) == *outcome)
+    }
+}
+
+impl Solver {
+    pub fn new<D: Into<Dictionary>>(dict: D) -> Self {
+        Self::with_strategy(dict, Entropy)
+    }
+}
+
+impl<T: Strategy> Solver<T> {
+    /// Create a solver which may play any guess in the dictionary, including words
+    /// which cannot be the answer when they reveal more.
+    pub fn with_strategy<D: Into<Dictionary>>(dict: D, strategy: T) -> Self {
+        let (matrix, weights) = dictionary_matrix(&dict.into());
+        Self::from_parts(matrix, weights, strategy)
+    }
+
+    /// Create a solver guessing and looking for answers among the words of the matrix,
+    /// which can be shared between solvers. Every answer is taken to be equally likely.
+    pub fn with_matrix(matrix: Arc<PatternMatrix>, strategy: T) -> Self {
+        let weights = uniform_weights(&matrix);
+        Self::from_parts(matrix, weights, strategy)
+    }
+
+    /// Create a solver for the words of the matrix, where `weights` gives the relative
+    /// likelihood of each of the matrix answers, in the same order. Weights must be
+    /// positive and finite.
+    pub fn with_weights(
+        matrix: Arc<PatternMatrix>,
+        weights: Arc<[f64]>,
+        strategy: T,
+    ) -> Result<Self, Error> {
+        check_weights(&matrix, &weights)?;
+        Ok(Self::from_parts(matrix, weights, strategy))
+    }
+
+    fn from_parts(matrix: Arc<PatternMatrix>, weights: Arc<[f64]>, strategy: T) -> Self {
+        let candidates = CandidateSet::full(matrix.answers().len());
+        let guesses = (0..matrix.guesses().len()).collect();
+        let mut solver = Self {
+            knowledge: Knowledge::new(),
+            matrix,
+            candidates,
+            weights,
+            guesses,
+            strategy,
+            filter: Filter::default(),
+            rules: GameRules::default(),
+            next_guess: None,
+        };
+        // The opening guess is the most expensive to compute, doing it up front
+        // lets it be shared by cloning the solver.
+        solver.next_guess = solver.select();
+        solver
+    }
+
+    /// Change how candidate answers are filtered. The constraints and consistency filters
+    /// are exact, so choosing between them affects only performance; the consistency filter
+    /// also serves as a check on the other. Only the lies filter copes with false outcomes.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Play by `rules`: in hard mode only guesses using every hint revealed so far are
+    /// played, and the last guess allowed is always a word which could be the answer,
+    /// unless the strategy chooses its own last guess.
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        let guesses = self.guesses.len();
+        let last_guess = self.guesses_left() == Some(1);
+        self.rules = rules;
+        self.reset_guesses();
+        // Suggestions only depend on the rules through the guesses allowed and
+        // whether this is the last guess
+        let changed =
+            self.guesses.len() != guesses || (self.guesses_left() == Some(1)) != last_guess;
+        if changed && self.next_guess.is_some() {
+            self.next_guess = self.select();
+        }
+        self
+    }
 
+    /// Only play guesses which use every hint revealed so far, as hard mode requires.
+    pub fn with_hard_mode(self, hard_mode: bool) -> Self {
+        let rules = GameRules {
+            hard_mode,
+            ..self.rules
+        };
+        self.with_rules(rules)
+    }
+
+    /// How many more guesses the rules allow, or `None` for no limit.
+    pub fn guesses_left(&self) -> Option<usize> {
+        self.rules.guesses_left(self.knowledge.guess_count())
+    }
+
+    /// Words which could still be the answer.
+    pub fn candidates(&self) -> impl Iterator<Item = Word> + '_ {
+        let answers = self.matrix.answers();
+        self.candidates.iter().map(move |i| answers[i])
+    }
+
+    /// The probability that `word` is the answer, given the prior weights of the
+    /// answers and everything learned so far.
+    pub fn probability(&self, word: &Word) -> f64 {
+        let weight = match self.matrix.answer_index(word) {
+            Some(a) if self.candidates.contains(a) => self.weights[a],
+            _ => return 0.0,
+        };
+        weight / self.candidates.iter().map(|a| self.weights[a]).sum::<f64>()
+    }
+
+    /// Submit the suggested word to the server and learn from the outcome.
+    pub fn guess<S: Server>(&mut self, server: &mut S) -> Result<(Word, GuessOutcome), Error> {
+        let guess = self.suggest().ok_or(Error::Stumped)?;
         let outcome = server.submit(guess)?;
-        self.guess_outcomes[self.guess_index] = Some(outcome);
-        self.guess_index += 1;
-
-        // Update knowledge about the letters
-        for (i, (x, y)) in guess.iter().zip(outcome.iter()).enumerate() {
-            let j = x.index() as usize;
-            match y {
-                LetterOutcome::Absent => match self.letters_state[j] {
-                    LetterState::Unknown => self.letters_state[j] = LetterState::Absent,
-                    LetterState::Positions(ref mut ps) => {
-                        ps[i] = PositionState::No;
-                        // We know Present -> Absent additionally means there is no further
-                        // duplicates of that letter, so we'll immediately filter out words
-                        // with too many instances of it.
-                        let letter_count = guess.count(x);
-                        self.dictionary.retain(|w| w.count(x) < letter_count);
-                    }
-                    // We knew were it was NOT located because of a correct letter, now
-                    // we have evidence it might be nowhere at all, which is stronger, so
-                    // we'll go with that.
-                    LetterState::AntiPositions(_) => self.letters_state[j] = LetterState::Absent,
-                    LetterState::Absent => (),
-                },
-                LetterOutcome::Present => match self.letters_state[j] {
-                    LetterState::Unknown => {
-                        let mut ps = [PositionState::Maybe; 5];
-                        ps[i] = PositionState::No;
-                        self.letters_state[j] = LetterState::Positions(ps);
-                    }
-                    LetterState::Positions(ref mut ps) => {
-                        ps[i] = PositionState::No;
-                    }
-                    LetterState::AntiPositions(ps) => {
-                        let mut new_ps = util::map_array(ps, PositionState::not);
-                        new_ps[i] = PositionState::No;
-                        self.letters_state[j] = LetterState::Positions(new_ps);
-                    }
-                    // If server is working properly, cannot go from Absent to Present
-                    LetterState::Absent => unreachable!(),
-                },
-                LetterOutcome::Correct => {
-                    // current letter is at position i
-                    match self.letters_state[j] {
-                        LetterState::Unknown => {
-                            let mut ps = [PositionState::Maybe; 5];
-                            ps[i] = PositionState::Yes;
-                            self.letters_state[j] = LetterState::Positions(ps);
-                        }
-                        LetterState::Positions(ref mut ps) => {
-                            ps[i] = PositionState::Yes;
-                        }
-                        LetterState::AntiPositions(ps) => {
-                            let mut new_ps = util::map_array(ps, PositionState::not);
-                            new_ps[i] = PositionState::Yes;
-                            self.letters_state[j] = LetterState::Positions(new_ps);
-                        }
-                        // Absent -> Correct is possible if we guessed a word that has the letter
-                        // multiple times, but the answer only has that letter once. In our guess
-                        // one instance of the letter will be in an incorrect position, and the other in
-                        // the correct position. Since there are no duplicates of that letter the former
-                        // will be seen as Absent, while the latter as Correct.
-                        LetterState::Absent => {
-                            let mut ps = [PositionState::No; 5];
-                            ps[i] = PositionState::Yes;
-                            self.letters_state[j] = LetterState::Positions(ps);
-                        }
-                    }
-                    // all other letters are not at position i
-                    for (k, s) in self.letters_state.iter_mut().enumerate() {
-                        if k == j {
-                            continue;
-                        }
-                        match s {
-                            LetterState::Absent => (), // nothing to change
-                            LetterState::Unknown => {
-                                let mut ps = [PositionState::Maybe; 5];
-                                ps[i] = PositionState::Yes;
-                                *s = LetterState::AntiPositions(ps);
-                            }
-                            LetterState::AntiPositions(ps) => {
-                                ps[i] = PositionState::Yes;
-                            }
-                            LetterState::Positions(ps) => {
-                                ps[i] = PositionState::No;
-                            }
-                        }
-                    }
-                }
-            }
+        self.observe(guess, outcome)?;
+        Ok((guess, outcome))
+    }
+
+    /// The word the solver would like to guess next, if any words remain.
+    pub fn suggest(&self) -> Option<Word> {
+        self.next_guess
+    }
+
+    /// Learn from the outcome of a guess which was played, whether or not it
+    /// was the suggested word. When no answer could have produced every outcome
+    /// seen so far, the outcome is still recorded so that it, or an earlier one,
+    /// can be corrected or retracted.
+    pub fn observe(&mut self, guess: Word, outcome: GuessOutcome) -> Result<(), Error> {
+        self.learn(guess, outcome);
+        self.update_suggestion()
+    }
+
+    /// Indices of the observed outcomes, in the order they were played, which
+    /// contradict the others: without any one of them some word could still be
+    /// the answer. Empty when the outcomes are consistent, or when no single
+    /// mistake explains them.
+    pub fn conflicts(&self) -> Vec<usize> {
+        if !self.candidates.is_empty() {
+            return Vec::new();
         }
+        conflicts(&self.matrix, self.filter, &self.knowledge.guess_outcomes)
+    }
 
-        // Filter dictionary based on information
-        let state = &self.letters_state;
-        self.dictionary.retain(|w| satisfies(w, state));
-        if self.dictionary.is_empty() && outcome != [LetterOutcome::Correct; 5] {
-            return Err(Error::Stumped);
+    /// Replace the outcome observed for the guess at `row`, counting from 0, and
+    /// learn everything again from the corrected history.
+    pub fn correct(&mut self, row: usize, outcome: GuessOutcome) -> Result<(), Error> {
+        self.check_row(row)?;
+        self.knowledge.guess_outcomes[row].1 = outcome;
+        self.replay()
+    }
+
+    /// Forget the guess at `row`, counting from 0, as if it had never been played,
+    /// and learn everything again from the rest of the history.
+    pub fn retract(&mut self, row: usize) -> Result<(), Error> {
+        self.check_row(row)?;
+        self.knowledge.guess_outcomes.remove(row);
+        self.replay()
+    }
+
+    fn check_row(&self, row: usize) -> Result<(), Error> {
+        if row < self.knowledge.guess_count() {
+            Ok(())
+        } else {
+            Err(Error::NoSuchRow(row))
         }
+    }
 
-        Ok((guess, outcome))
+    /// Start again from no knowledge and learn from each recorded outcome in turn.
+    fn replay(&mut self) -> Result<(), Error> {
+        let history = std::mem::take(&mut self.knowledge.guess_outcomes);
+        self.knowledge = Knowledge::new();
+        self.candidates = CandidateSet::full(self.matrix.answers().len());
+        self.guesses = (0..self.matrix.guesses().len()).collect();
+        for (guess, outcome) in history {
+            self.learn(guess, outcome);
+        }
+        self.update_suggestion()
+    }
+
+    fn learn(&mut self, guess: Word, outcome: GuessOutcome) {
+        self.knowledge
+            .record(guess, outcome, self.filter.trusts_outcomes());
+        if let Some(i) = self.matrix.guess_index(&guess) {
+            self.guesses.retain(|g| *g != i);
+        }
+        self.restrict_guesses();
+        self.filter.apply(
+            &mut self.candidates,
+            &self.matrix,
+            &self.knowledge,
+            (guess, outcome),
+        );
+    }
+
+    /// Choose the next guess after learning from the latest outcome.
+    fn update_suggestion(&mut self) -> Result<(), Error> {
+        let won = self
+            .knowledge
+            .guess_outcomes
+            .last()
+            .map_or(false, |(_, outcome)| {
+                *outcome == [LetterOutcome::Correct; 5]
+            });
+        if won {
+            self.next_guess = None;
+        } else if self.candidates.is_empty() {
+            self.next_guess = None;
+            return Err(Error::Contradiction(self.conflicts()));
+        } else {
+            self.next_guess = self.select();
+        }
+
+        Ok(())
+    }
+
+    fn select(&mut self) -> Option<Word> {
+        let context = Context {
+            matrix: &self.matrix,
+            candidates: &self.candidates,
+            weights: &self.weights,
+            guesses: &self.guesses,
+            knowledge: &self.knowledge,
+        };
+        // With one guess left, only a word which could be the answer can win
+        if self.guesses_left() == Some(1) && !self.strategy.chooses_last_guess() {
+            return context.likeliest();
+        }
+        self.strategy.select(&context)
+    }
+
+    /// Allow every guess which has not been played yet, then apply the rules.
+    fn reset_guesses(&mut self) {
+        let matrix = &self.matrix;
+        let played: Vec<usize> = self
+            .knowledge
+            .outcomes()
+            .filter_map(|(guess, _)| matrix.guess_index(guess))
+            .collect();
+        self.guesses = (0..matrix.guesses().len())
+            .filter(|g| !played.contains(g))
+            .collect();
+        self.restrict_guesses();
+    }
+
+    /// Remove the guesses which break the rules from those the solver may play.
+    fn restrict_guesses(&mut self) {
+        if self.rules.hard_mode {
+            let hints = &self.knowledge.hints;
+            let words = self.matrix.guesses();
+            self.guesses
+                .retain(|g| hints.ignored_by(&words[*g]).is_none());
+        }
     }
 }
 
-fn satisfies(word: &Word, state: &[LetterState; 26]) -> bool {
-    for (i, l) in word.iter().enumerate() {
-        let j = l.index();
-        match state[j as usize] {
-            LetterState::Absent => return false,
-            LetterState::Unknown => (), // not sure
-            LetterState::Positions(ps) => {
-                match ps[i] {
-                    PositionState::Yes => (),   // definitely right
-                    PositionState::Maybe => (), // not sure
-                    PositionState::No => return false,
-                }
+/// Which board of a multi-board game a guess was chosen for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The guess tells the most about all of the unsolved boards together
+    All,
+    /// The guess is the only word left which could be the answer on this board
+    Board(usize),
+}
+
+/// The matrix of the dictionary's guesses and answers, with the weight of each answer.
+fn dictionary_matrix(dict: &Dictionary) -> (Arc<PatternMatrix>, Arc<[f64]>) {
+    let guesses = dict.guesses().iter().copied().collect();
+    let answers = dict.answers().iter().copied().collect();
+    let matrix = PatternMatrix::new(guesses, answers);
+    let weights = matrix.answers().iter().map(|a| dict.weight(a)).collect();
+    (Arc::new(matrix), weights)
+}
+
+/// Indices of the `rows` without any one of which some answer of the matrix would
+/// still be consistent with the others.
+fn conflicts(matrix: &PatternMatrix, filter: Filter, rows: &[(Word, GuessOutcome)]) -> Vec<usize> {
+    (0..rows.len())
+        .filter(|&skip| {
+            let mut knowledge = Knowledge::new();
+            let mut candidates = CandidateSet::full(matrix.answers().len());
+            for (_, &(guess, outcome)) in rows.iter().enumerate().filter(|(i, _)| *i != skip) {
+                knowledge.record(guess, outcome, filter.trusts_outcomes());
+                filter.apply(&mut candidates, matrix, &knowledge, (guess, outcome));
             }
-            LetterState::AntiPositions(ps) => match ps[i] {
-                PositionState::Yes => return false,
-                PositionState::Maybe => (),
-                PositionState::No => (),
+            !candidates.is_empty()
+        })
+        .collect()
+}
+
+/// The same weight for each of the matrix answers.
+fn uniform_weights(matrix: &PatternMatrix) -> Arc<[f64]> {
+    Arc::from(vec![1.0; matrix.answers().len()])
+}
+
+/// Check that there is a valid weight for each of the matrix answers.
+fn check_weights(matrix: &PatternMatrix, weights: &[f64]) -> Result<(), Error> {
+    if weights.len() != matrix.answers().len() {
+        return Err(Error::WeightCount(weights.len()));
+    }
+    match weights
+        .iter()
+        .position(|w| !dictionary::is_valid_weight(*w))
+    {
+        Some(i) => Err(Error::InvalidWeight(i)),
+        None => Ok(()),
+    }
+}
+
+/// One board of a multi-board game, as seen by the solver.
+#[derive(Debug, Clone)]
+struct Board {
+    knowledge: Knowledge,
+    /// Indices of the matrix answers which could still be the answer
+    candidates: CandidateSet,
+    solved: bool,
+}
+
+/// Solves several boards at once, as served by a `MultiServer`. Guesses are chosen for
+/// the information they give about all of the unsolved boards combined, except that a
+/// board is finished off as soon as there is only one word left for it.
+#[derive(Debug, Clone)]
+pub struct MultiSolver {
+    matrix: Arc<PatternMatrix>,
+    /// Prior likelihood of each matrix answer, by index
+    weights: Arc<[f64]>,
+    boards: Vec<Board>,
+    /// Indices of the matrix guesses which have not been played, whatever the rules
+    guesses: Vec<usize>,
+    guess_count: usize,
+    filter: Filter,
+    /// The rules of the game as a whole, with the guess limit shared by all boards
+    rules: GameRules,
+    next_guess: Option<(Word, Target)>,
+}
+
+impl MultiSolver {
+    /// Create a solver for `boards` boards, as `Solver::with_strategy` does for one.
+    pub fn new<D: Into<Dictionary>>(dict: D, boards: usize) -> Self {
+        let (matrix, weights) = dictionary_matrix(&dict.into());
+        Self::from_parts(matrix, weights, boards)
+    }
+
+    /// Create a solver for `boards` boards, as `Solver::with_matrix` does for one.
+    pub fn with_matrix(matrix: Arc<PatternMatrix>, boards: usize) -> Self {
+        let weights = uniform_weights(&matrix);
+        Self::from_parts(matrix, weights, boards)
+    }
+
+    /// Create a solver for `boards` boards, as `Solver::with_weights` does for one.
+    pub fn with_weights(
+        matrix: Arc<PatternMatrix>,
+        weights: Arc<[f64]>,
+        boards: usize,
+    ) -> Result<Self, Error> {
+        check_weights(&matrix, &weights)?;
+        Ok(Self::from_parts(matrix, weights, boards))
+    }
+
+    fn from_parts(matrix: Arc<PatternMatrix>, weights: Arc<[f64]>, boards: usize) -> Self {
+        let board = Board {
+            knowledge: Knowledge::new(),
+            candidates: CandidateSet::full(matrix.answers().len()),
+            solved: false,
+        };
+        let guesses = (0..matrix.guesses().len()).collect();
+        let mut solver = Self {
+            matrix,
+            weights,
+            boards: vec![board; boards],
+            guesses,
+            guess_count: 0,
+            filter: Filter::default(),
+            rules: GameRules {
+                max_guesses: Some(boards + 5),
+                ..GameRules::default()
             },
+            next_guess: None,
+        };
+        solver.next_guess = solver.select();
+        solver
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Play by `rules`, where the guess limit applies to the game as a whole and in hard
+    /// mode guesses must use the hints of every unsolved board. By default five more
+    /// guesses are allowed than there are boards, as `MultiBoardServer` does.
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.rules = rules;
+        if self.next_guess.is_some() {
+            self.next_guess = self.select();
         }
+        self
     }
-    // Check everything that is present is in the candidate word
-    for (l, s) in Letter::LETTERS.iter().zip(state.iter()) {
-        if let LetterState::Positions(_) = s {
-            if !word.contains(l) {
-                return false;
+
+    /// How many more guesses the rules allow, or `None` for no limit.
+    pub fn guesses_left(&self) -> Option<usize> {
+        self.rules.guesses_left(self.guess_count)
+    }
+
+    pub fn boards(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn is_solved(&self, board: usize) -> bool {
+        self.boards[board].solved
+    }
+
+    /// Words which could still be the answer on `board`.
+    pub fn candidates(&self, board: usize) -> impl Iterator<Item = Word> + '_ {
+        let answers = self.matrix.answers();
+        self.boards[board]
+            .candidates
+            .iter()
+            .map(move |i| answers[i])
+    }
+
+    /// Submit the suggested word to the server and learn from the outcomes,
+    /// returning the guess, the board it was chosen for and its outcomes.
+    pub fn guess<S: MultiServer>(
+        &mut self,
+        server: &mut S,
+    ) -> Result<(Word, Target, Vec<Option<GuessOutcome>>), Error> {
+        let (guess, target) = self.suggest().ok_or(Error::Stumped)?;
+        let outcomes = server.submit(guess)?;
+        self.observe(guess, &outcomes)?;
+        Ok((guess, target, outcomes))
+    }
+
+    /// The word the solver would like to guess next and the board it is for,
+    /// if any boards are unsolved.
+    pub fn suggest(&self) -> Option<(Word, Target)> {
+        self.next_guess
+    }
+
+    /// Learn from the outcomes of a guess on each board, with `None` for boards
+    /// which were already solved. When no word is left for some board, the error gives
+    /// the guesses contradicting its other outcomes as `Solver::conflicts` does, counting
+    /// from 0 in the order they were played.
+    pub fn observe(&mut self, guess: Word, outcomes: &[Option<GuessOutcome>]) -> Result<(), Error> {
+        if outcomes.len() != self.boards.len() {
+            return Err(Error::BoardCount(outcomes.len()));
+        }
+        if let Some(i) = self.matrix.guess_index(&guess) {
+            self.guesses.retain(|g| *g != i);
+        }
+        self.guess_count += 1;
+
+        let mut contradiction = None;
+        for (board, outcome) in self.boards.iter_mut().zip(outcomes.iter()) {
+            let outcome = match outcome {
+                Some(outcome) if !board.solved => *outcome,
+                _ => continue,
+            };
+            board
+                .knowledge
+                .record(guess, outcome, self.filter.trusts_outcomes());
+            self.filter.apply(
+                &mut board.candidates,
+                &self.matrix,
+                &board.knowledge,
+                (guess, outcome),
+            );
+            if outcome == [LetterOutcome::Correct; 5] {
+                board.solved = true;
+            } else if board.candidates.is_empty() && contradiction.is_none() {
+                let rows = &board.knowledge.guess_outcomes;
+                contradiction = Some(conflicts(&self.matrix, self.filter, rows));
+            }
+        }
+        if let Some(rows) = contradiction {
+            self.next_guess = None;
+            return Err(Error::Contradiction(rows));
+        }
+        self.next_guess = self.select();
+
+        Ok(())
+    }
+
+    fn select(&self) -> Option<(Word, Target)> {
+        let unsolved = || self.boards.iter().enumerate().filter(|(_, b)| !b.solved);
+        // In hard mode a guess must use the hints of every board still being played
+        let words = self.matrix.guesses();
+        let allowed = |g: usize| {
+            !self.rules.hard_mode
+                || unsolved().all(|(_, b)| b.knowledge.hints.ignored_by(&words[g]).is_none())
+        };
+        let guesses: Vec<usize> = self
+            .guesses
+            .iter()
+            .copied()
+            .filter(|g| allowed(*g))
+            .collect();
+
+        // Finish off boards with one word left. With one guess left only one more board
+        // can be won, so play the likeliest word of the first
+        let last_guess = self.guesses_left() == Some(1);
+        let answers = self.matrix.answers();
+        for (i, board) in unsolved() {
+            if board.candidates.len() == 1 || last_guess {
+                let best = board
+                    .candidates
+                    .iter()
+                    .filter(|a| {
+                        let g = self.matrix.guess_index(&answers[*a]);
+                        g.map_or(false, |g| guesses.binary_search(&g).is_ok())
+                    })
+                    .max_by(|a, b| {
+                        // Prefer the first of equally likely words
+                        let (x, y) = (self.weights[*a], self.weights[*b]);
+                        x.partial_cmp(&y).unwrap_or(Ordering::Equal).then(b.cmp(a))
+                    });
+                if let Some(a) = best {
+                    return Some((answers[a], Target::Board(i)));
+                }
             }
         }
+
+        let contexts: Vec<Context> = self
+            .boards
+            .iter()
+            .filter(|b| !b.solved)
+            .map(|b| Context {
+                matrix: &self.matrix,
+                candidates: &b.candidates,
+                weights: &self.weights,
+                guesses: &guesses,
+                knowledge: &b.knowledge,
+            })
+            .collect();
+        let totals: Vec<f64> = contexts.iter().map(Context::total_weight).collect();
+        if contexts.is_empty() {
+            return None;
+        }
+        // The boards are independent, so the information gained on each adds up, as
+        // does the chance of the guess solving each of them
+        let best = strategy::best_guess_index(&guesses, |guess| {
+            contexts
+                .iter()
+                .zip(totals.iter())
+                .map(|(c, total)| {
+                    let information = strategy::entropy(&c.weighted_buckets(guess), *total);
+                    (information, c.candidate_weight(guess) / total)
+                })
+                .fold((0.0, 0.0), |(i, p), (x, q)| (i + x, p + q))
+        });
+        best.map(|g| (self.matrix.guesses()[g], Target::All))
     }
-    true
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum LetterState {
-    /// No information yet
-    Unknown,
-    /// Definitely know the letter is in at least some positions
-    Positions([PositionState; 5]),
-    /// Definitely know the letter is not in some positions
-    AntiPositions([PositionState; 5]),
-    /// Definitely know the letter is not in the word at all
-    Absent,
+/// What is known about the answer: which letters may appear at each position,
+/// and how many times each letter may appear overall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    /// Letters allowed at each position, as bit sets indexed by `Letter::index`
+    allowed: [u32; 5],
+    min_count: [u8; 26],
+    max_count: [u8; 26],
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum PositionState {
-    Yes,
-    Maybe,
-    No,
-}
+impl Constraints {
+    const ALL_LETTERS: u32 = (1 << 26) - 1;
 
-impl PositionState {
-    pub fn not(self) -> Self {
-        match self {
-            Self::Yes => Self::No,
-            Self::No => Self::Yes,
-            Self::Maybe => Self::Maybe,
+    pub fn new() -> Self {
+        Self {
+            allowed: [Self::ALL_LETTERS; 5],
+            min_count: [0; 26],
+            max_count: [5; 26],
+        }
+    }
+
+    /// Whether `letter` may appear at `position` in the answer.
+    pub fn allows(&self, position: usize, letter: Letter) -> bool {
+        self.allowed[position] & (1 << letter.index()) != 0
+    }
+
+    /// The fewest times `letter` can appear in the answer.
+    pub fn min_count(&self, letter: Letter) -> u8 {
+        self.min_count[letter.index() as usize]
+    }
+
+    /// The most times `letter` can appear in the answer.
+    pub fn max_count(&self, letter: Letter) -> u8 {
+        self.max_count[letter.index() as usize]
+    }
+
+    /// Narrow the constraints using the outcome of a guess.
+    pub fn update(&mut self, guess: Word, outcome: GuessOutcome) {
+        let mut found = [0u8; 26];
+        let mut absent = [false; 26];
+        for (i, (l, o)) in guess.iter().zip(outcome.iter()).enumerate() {
+            let j = l.index() as usize;
+            match o {
+                LetterOutcome::Correct => {
+                    self.allowed[i] &= 1 << j;
+                    found[j] += 1;
+                }
+                LetterOutcome::Present => {
+                    self.allowed[i] &= !(1 << j);
+                    found[j] += 1;
+                }
+                LetterOutcome::Absent => {
+                    self.allowed[i] &= !(1 << j);
+                    absent[j] = true;
+                }
+            }
+        }
+        for j in 0..26 {
+            self.min_count[j] = self.min_count[j].max(found[j]);
+            // An absent letter means the answer has no more copies than were found
+            if absent[j] {
+                self.max_count[j] = self.max_count[j].min(found[j]);
+            }
         }
     }
+
+    /// Whether `word` could be the answer.
+    pub fn satisfies(&self, word: &Word) -> bool {
+        let mut counts = [0u8; 26];
+        for (i, l) in word.iter().enumerate() {
+            if !self.allows(i, *l) {
+                return false;
+            }
+            counts[l.index() as usize] += 1;
+        }
+        counts
+            .iter()
+            .zip(self.min_count.iter().zip(self.max_count.iter()))
+            .all(|(c, (min, max))| min <= c && c <= max)
+    }
+
+    /// Whether any word at all could satisfy the constraints. This is false
+    /// only if the outcomes used to build them contradict each other.
+    pub fn is_consistent(&self) -> bool {
+        let required: u8 = self.min_count.iter().sum();
+        self.allowed.iter().all(|a| *a != 0)
+            && self
+                .min_count
+                .iter()
+                .zip(self.max_count.iter())
+                .all(|(min, max)| min <= max)
+            && required <= 5
+    }
 }
 
-impl Default for PositionState {
+impl Default for Constraints {
     fn default() -> Self {
-        Self::Maybe
+        Self::new()
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     Stumped,
+    /// No answer could have produced every outcome observed, the indices of those
+    /// which could be mistaken are given as by `Solver::conflicts`
+    Contradiction(Vec<usize>),
+    /// There is no observed outcome with this index
+    NoSuchRow(usize),
+    /// This many outcomes were given for a guess, rather than one for every board
+    BoardCount(usize),
+    /// This many weights were given, rather than one for every answer
+    WeightCount(usize),
+    /// The weight of the answer with this index is not positive and finite
+    InvalidWeight(usize),
     Server(server::Error),
 }
 
@@ -210,14 +825,26 @@ impl From<server::Error> for Error {
 
 #[cfg(test)]
 mod tests {
-    use crate::{server, solver, LetterOutcome, Word};
+    use crate::dictionary::Dictionary;
+    use crate::matrix::PatternMatrix;
+    use crate::rules::GameRules;
+    use crate::server::{Game, Hints, LyingServer, MultiBoardServer, MultiServer, Status};
+    use crate::solver::{Constraints, Filter, MultiSolver, Target};
+    use crate::strategy::{Entropy, Minimax, Strategy};
+    use crate::util::words;
+    use crate::{score, server, solver, GuessOutcome, Letter, LetterOutcome, Word};
     use rand::seq::IteratorRandom;
-    use std::collections::HashSet;
+    use std::fmt;
+    use std::sync::Arc;
 
     #[test]
     fn test_solver() {
-        let dict = load_dictionary();
-        let word = *dict.iter().choose(&mut rand::thread_rng()).unwrap();
+        let dict = Dictionary::embedded();
+        let word = *dict
+            .answers()
+            .iter()
+            .choose(&mut rand::thread_rng())
+            .unwrap();
 
         let mut server = server::InMemoryServer::new(word, dict.clone());
         let mut solver = solver::Solver::new(dict);
@@ -232,44 +859,489 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_suggest_observe() {
+        let dict = Dictionary::embedded();
+        let word = Word::try_from_str("snout").unwrap();
+        let mut solver = solver::Solver::new(dict);
+
+        // Play a word of our own choosing first, then follow the suggestions
+        let mut guess = Word::try_from_str("fuzzy").unwrap();
+        for _ in 0..6 {
+            let outcome = score(guess, word);
+            solver.observe(guess, outcome).unwrap();
+            if outcome == [LetterOutcome::Correct; 5] {
+                assert_eq!(solver.suggest(), None);
+                return;
+            }
+            guess = solver.suggest().unwrap();
+        }
+        panic!("Failed to find {:?}", word);
+    }
+
+    #[test]
+    fn test_game_rules() {
+        // More guesses than the original game allows can be observed
+        let word = Word::try_from_str("snout").unwrap();
+        let unlimited = GameRules {
+            max_guesses: None,
+            ..GameRules::default()
+        };
+        let mut solver = solver::Solver::new(Dictionary::embedded()).with_rules(unlimited);
+        let guesses = words(&[
+            "fuzzy", "crane", "blimp", "dwell", "eight", "knoll", "sassy", "whack",
+        ]);
+        for guess in guesses {
+            solver.observe(guess, score(guess, word)).unwrap();
+        }
+        assert_eq!(solver.knowledge.guess_count(), 8);
+        assert_eq!(solver.guesses_left(), None);
+        assert!(solver.candidates().any(|w| w == word));
+
+        // The last guess allowed is always one which could win
+        let answers = words(&[
+            "batch", "catch", "hatch", "latch", "match", "patch", "watch",
+        ]);
+        let dict = Dictionary::new(answers.clone(), words(&["blimp"]));
+        let solver = solver::Solver::new(dict);
+        assert_eq!(solver.suggest(), Some(words(&["blimp"])[0]));
+        let one_guess = GameRules {
+            max_guesses: Some(1),
+            ..GameRules::default()
+        };
+        let solver = solver.with_rules(one_guess);
+        assert_eq!(solver.guesses_left(), Some(1));
+        assert_eq!(solver.suggest(), Some(answers[0]));
+
+        // Switching to hard mode part way through a game changes the suggestion
+        let mut solver = solver::Solver::new(Dictionary::embedded());
+        let guess = solver.suggest().unwrap();
+        solver.observe(guess, score(guess, word)).unwrap();
+        let ignores_hints = |solver: &solver::Solver, guess: Option<Word>| {
+            let hints = solver.knowledge.hints();
+            hints.ignored_by(&guess.unwrap()).is_some()
+        };
+        assert!(ignores_hints(&solver, solver.suggest()));
+        let solver = solver.with_hard_mode(true);
+        assert!(!ignores_hints(&solver, solver.suggest()));
+    }
+
+    #[test]
+    fn test_correct_outcomes() {
+        use LetterOutcome::Absent;
+        let word = Word::try_from_str("snout").unwrap();
+        let sheep = Word::try_from_str("sheep").unwrap();
+        let shout = Word::try_from_str("shout").unwrap();
+        let mut solver = solver::Solver::new(Dictionary::embedded());
+        // Rows which were never observed are rejected without changing anything
+        let opening = solver.suggest();
+        assert_eq!(
+            solver.correct(0, score(sheep, word)),
+            Err(solver::Error::NoSuchRow(0))
+        );
+        assert_eq!(solver.retract(3), Err(solver::Error::NoSuchRow(3)));
+        assert_eq!(solver.knowledge.guess_count(), 0);
+        assert_eq!(solver.suggest(), opening);
+
+        solver.observe(sheep, score(sheep, word)).unwrap();
+        assert!(solver.conflicts().is_empty());
+
+        // Mistyping the first letter as absent contradicts the first outcome,
+        // either of which could be the mistake
+        let mut mistyped = score(shout, word);
+        mistyped[0] = Absent;
+        assert_eq!(
+            solver.observe(shout, mistyped),
+            Err(solver::Error::Contradiction(vec![0, 1]))
+        );
+        assert_eq!(solver.conflicts(), vec![0, 1]);
+        assert_eq!(solver.suggest(), None);
+
+        solver.correct(1, score(shout, word)).unwrap();
+        assert!(solver.conflicts().is_empty());
+        assert!(solver.candidates().any(|w| w == word));
+        assert!(solver.suggest().is_some());
+        let candidates = solver.candidates().count();
+
+        assert_eq!(
+            solver.correct(2, mistyped),
+            Err(solver::Error::NoSuchRow(2))
+        );
+        assert_eq!(solver.retract(2), Err(solver::Error::NoSuchRow(2)));
+        assert_eq!(solver.knowledge.guess_count(), 2);
+        assert_eq!(solver.candidates().count(), candidates);
+
+        solver.retract(1).unwrap();
+        assert_eq!(solver.knowledge.guess_count(), 1);
+        assert!(solver.candidates().count() > candidates);
+        assert!(solver
+            .candidates()
+            .all(|w| solver.knowledge.consistent_with(&w)));
+        assert!(solver
+            .guesses
+            .contains(&solver.matrix.guess_index(&shout).unwrap()));
+    }
+
+    #[test]
+    fn test_probe_words() {
+        let answers = words(&[
+            "batch", "catch", "hatch", "latch", "match", "patch", "watch",
+        ]);
+        let dict = Dictionary::new(answers.clone(), words(&["blimp"]));
+        let solver = solver::Solver::new(dict.clone());
+
+        // Guessing the answers one at a time could take seven guesses, a probe
+        // word separating four of them at once does much better.
+        assert_eq!(solver.suggest(), Word::try_from_str("blimp"));
+        for answer in answers {
+            assert!(run_solver(answer, dict.clone(), solver.clone()) <= 4);
+        }
+    }
+
+    #[test]
+    fn test_no_answers() {
+        let blimp = words(&["blimp"])[0];
+        let mut solver = solver::Solver::new(Dictionary::new(vec![], vec![blimp]));
+        // With nothing to find, observing fails without a panic
+        assert!(solver.observe(blimp, [LetterOutcome::Absent; 5]).is_err());
+        assert_eq!(solver.suggest(), None);
+    }
+
+    #[test]
+    fn test_constraints_letter_counts() {
+        let e = Letter::new(b'e').unwrap();
+        let mut constraints = Constraints::new();
+        // Answer "sheep": one 'e' is present, one correct and the third absent
+        let guess = Word::try_from_str("geese").unwrap();
+        let outcome = score(guess, Word::try_from_str("sheep").unwrap());
+        constraints.update(guess, outcome);
+        assert_eq!(constraints.min_count(e), 2);
+        assert_eq!(constraints.max_count(e), 2);
+        assert!(constraints.allows(2, e));
+        assert!(!constraints.allows(1, e));
+        assert!(constraints.satisfies(&Word::try_from_str("sheep").unwrap()));
+        assert!(!constraints.satisfies(&Word::try_from_str("spree").unwrap()));
+        assert!(constraints.is_consistent());
+
+        // Feedback contradicting what came before is detected
+        constraints.update(guess, [LetterOutcome::Absent; 5]);
+        assert!(!constraints.is_consistent());
+    }
+
+    #[test]
+    fn test_constraints_exact() {
+        let dict = Dictionary::embedded();
+        let sequences = [
+            ["eerie", "geese", "llama"],
+            ["mamma", "eaten", "sissy"],
+            ["raise", "speed", "geese"],
+        ];
+        for sequence in sequences.iter() {
+            let guesses = words(sequence);
+            for answer in dict.answers().iter() {
+                let mut constraints = Constraints::new();
+                let outcomes: Vec<GuessOutcome> = guesses
+                    .iter()
+                    .map(|g| {
+                        let outcome = score(*g, *answer);
+                        constraints.update(*g, outcome);
+                        outcome
+                    })
+                    .collect();
+                assert!(constraints.is_consistent());
+                for word in dict.answers().iter() {
+                    let consistent = guesses
+                        .iter()
+                        .zip(outcomes.iter())
+                        .all(|(g, o)| score(*g, *word) == *o);
+                    assert_eq!(constraints.satisfies(word), consistent);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_consistency_filter() {
+        let dict = Dictionary::embedded();
+        let consistency = solver::Solver::new(dict.clone());
+        let constraints = consistency.clone().with_filter(Filter::Constraints);
+        for word in dict.answers().iter().step_by(23) {
+            let mut server = server::InMemoryServer::new(*word, dict.clone());
+            let mut constraints = constraints.clone();
+            let mut consistency = consistency.clone();
+            loop {
+                let (guess, outcome) = constraints.guess(&mut server).unwrap();
+                consistency.observe(guess, outcome).unwrap();
+                assert!(constraints.candidates().eq(consistency.candidates()));
+                assert!(consistency
+                    .candidates()
+                    .all(|w| consistency.knowledge.consistent_with(&w)));
+                if outcome == [LetterOutcome::Correct; 5] {
+                    break;
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_average_guesses() {
-        let dict = load_dictionary();
-        let mut total: u16 = 0;
-        let mut count: u16 = 0;
-        let mut fail_count: u16 = 0;
-        for word in dict.iter() {
-            count += 1;
-            let result = run_solver(*word, dict.clone()) as u16;
-            total += result;
-            if result > 6 {
-                fail_count += 1;
+        let dict = Dictionary::embedded();
+        let solver = solver::Solver::new(dict.clone());
+        let fail_count = benchmark(&dict, solver);
+        assert_eq!(fail_count, 0);
+    }
+
+    #[test]
+    fn test_average_guesses_minimax() {
+        let dict = Dictionary::embedded();
+        let solver = solver::Solver::with_strategy(dict.clone(), Minimax);
+        let fail_count = benchmark(&dict, solver);
+        assert_eq!(fail_count, 0);
+    }
+
+    #[test]
+    fn test_average_guesses_hard_mode() {
+        let dict = Dictionary::embedded();
+        let solver = solver::Solver::new(dict.clone()).with_hard_mode(true);
+        println!("Hard mode:");
+        let fail_count = benchmark(&dict, solver);
+        // Some answers, like those ending in "ight", have too many neighbours to be
+        // told apart in time once the guesses are forced to follow the hints. About
+        // half a percent of games are lost, only a regression would lose one percent.
+        let limit = dict.len() / 100;
+        assert!(fail_count as usize <= limit, "{} failures", fail_count);
+    }
+
+    #[test]
+    fn test_invalid_weights() {
+        let answers = words(&["batch", "catch"]);
+        let matrix = Arc::new(PatternMatrix::new(answers.clone(), answers));
+        let cases = [
+            (vec![0.0, 0.0], solver::Error::InvalidWeight(0)),
+            (vec![1.0, f64::NAN], solver::Error::InvalidWeight(1)),
+            (vec![1.0], solver::Error::WeightCount(1)),
+        ];
+        for (weights, error) in cases {
+            let weights: Arc<[f64]> = Arc::from(weights);
+            let solver = solver::Solver::with_weights(matrix.clone(), weights.clone(), Entropy);
+            let multi = MultiSolver::with_weights(matrix.clone(), weights, 2);
+            let solver_error = solver.err();
+            assert_eq!(multi.err(), solver_error);
+            assert_eq!(solver_error, Some(error));
+        }
+        let weights: Arc<[f64]> = Arc::from(vec![1.0, 2.0]);
+        assert!(solver::Solver::with_weights(matrix.clone(), weights.clone(), Entropy).is_ok());
+        assert!(MultiSolver::with_weights(matrix, weights, 2).is_ok());
+    }
+
+    #[test]
+    fn test_multi_solver() {
+        let dict = Dictionary::embedded();
+        let solver = MultiSolver::new(dict.clone(), 4);
+        let mut answers: Vec<Word> = dict.iter().copied().collect();
+        answers.sort_unstable();
+
+        let mut total = 0;
+        let mut games = 0;
+        for game in answers.chunks(4).step_by(29) {
+            let mut server = MultiBoardServer::new(game, dict.clone());
+            let mut solver = solver.clone();
+            while server.can_guess() {
+                let (_, target, outcomes) = solver.guess(&mut server).unwrap();
+                if let Target::Board(b) = target {
+                    assert_eq!(outcomes[b], Some([LetterOutcome::Correct; 5]));
+                }
+                total += 1;
             }
+            assert!((0..4).all(|b| server.is_solved(b) && solver.is_solved(b)));
+            games += 1;
         }
-        let ratio = f64::from(total) / f64::from(count);
-        println!("Average guesses to solve: {}", ratio);
-        let ratio = f64::from(fail_count) / f64::from(count);
-        println!("Failure rate: {}", ratio);
+        println!("Average guesses to solve: {}", total as f64 / games as f64);
+
+        // In hard mode every guess uses the hints of all the unsolved boards, so none
+        // are rejected
+        let hard_mode = GameRules {
+            max_guesses: None,
+            hard_mode: true,
+            ..GameRules::default()
+        };
+        let hard = MultiSolver::new(dict.clone(), 2).with_rules(hard_mode);
+        let mut finished = 0;
+        let mut games = 0;
+        for game in answers.chunks(2).step_by(59) {
+            let mut server = MultiBoardServer::new(game, dict.clone()).with_rules(hard_mode);
+            let mut solver = hard.clone();
+            // The hints of different boards can conflict, leaving no word to play
+            while let Some((guess, _)) = solver.suggest() {
+                let outcomes = server.submit(guess);
+                assert!(outcomes.is_ok(), "{} rejected: {:?}", guess, outcomes);
+                solver.observe(guess, &outcomes.unwrap()).unwrap();
+            }
+            if (0..2).all(|b| server.is_solved(b)) {
+                finished += 1;
+            }
+            games += 1;
+        }
+        println!("Finished {} of {} hard mode games", finished, games);
+        assert!(finished * 4 >= games);
+
+        let mut solver = hard;
+        let guess = Word::try_from_str("crane").unwrap();
+        assert_eq!(
+            solver.observe(guess, &[None, None, None]),
+            Err(solver::Error::BoardCount(3))
+        );
+        assert_eq!(solver.guesses_left(), None);
+
+        // Contradictions are reported as by a single board solver
+        let answers = words(&["batch", "catch", "hatch"]);
+        let mut solver = MultiSolver::new(Dictionary::new(answers.clone(), vec![]), 2);
+        let outcomes = [
+            Some(score(answers[0], answers[1])),
+            Some(score(answers[0], answers[2])),
+        ];
+        solver.observe(answers[0], &outcomes).unwrap();
+        let outcomes = [
+            Some([LetterOutcome::Absent; 5]),
+            Some(score(answers[1], answers[2])),
+        ];
+        assert_eq!(
+            solver.observe(answers[1], &outcomes),
+            Err(solver::Error::Contradiction(vec![1]))
+        );
+        assert_eq!(solver.suggest(), None);
+    }
+
+    #[test]
+    fn test_lies_filter() {
+        let dict = Dictionary::embedded();
+        let unlimited = GameRules {
+            max_guesses: None,
+            ..GameRules::default()
+        };
+        let solver = solver::Solver::new(dict.clone())
+            .with_filter(Filter::Lies(1))
+            .with_rules(unlimited);
+        let mut answers: Vec<Word> = dict.iter().copied().collect();
+        answers.sort_unstable();
+
+        let mut total = 0;
+        let mut games = 0;
+        for (seed, answer) in answers.iter().step_by(97).enumerate() {
+            let mut server =
+                LyingServer::new(*answer, dict.clone(), seed as u64).with_rules(unlimited);
+            let mut solver = solver.clone();
+            loop {
+                let (_, outcome) = solver.guess(&mut server).unwrap();
+                total += 1;
+                if outcome == [LetterOutcome::Correct; 5] {
+                    break;
+                }
+                // The answer is never ruled out by a lie
+                assert!(solver.candidates().any(|w| w == *answer));
+            }
+            games += 1;
+        }
+        println!("Average guesses to solve: {}", total as f64 / games as f64);
+
+        // Outcomes which may be lies are not taken as constraints, but their hints
+        // must still be used in hard mode
+        let hard = solver.with_hard_mode(true);
+        let mut solver = hard.clone();
+        let guess = solver.suggest().unwrap();
+        let outcome = score(guess, answers[0]);
+        solver.observe(guess, outcome).unwrap();
+        assert_eq!(*solver.knowledge.constraints(), Constraints::new());
+        let mut hints = Hints::new();
+        hints.update(&guess, &outcome);
+        assert_eq!(*solver.knowledge.hints(), hints);
+
+        // So every guess is accepted by a lying server in hard mode. Games are only
+        // lost once a lie leaves a hint which the answer itself does not use
+        let mut won = 0;
+        let mut unwinnable = 0;
+        for (seed, answer) in answers.iter().step_by(97).enumerate() {
+            let mut server = LyingServer::new(*answer, dict.clone(), seed as u64)
+                .with_rules(unlimited)
+                .with_hard_mode(true);
+            let mut solver = hard.clone();
+            for _ in 0..20 {
+                match solver.guess(&mut server) {
+                    Ok((_, outcome)) if outcome == [LetterOutcome::Correct; 5] => break,
+                    Ok(_) => {}
+                    Err(solver::Error::Stumped) => break,
+                    Err(e) => panic!("{}: {:?}", answer, e),
+                }
+            }
+            let mut hints = Hints::new();
+            for (guess, outcome) in server.history() {
+                hints.update(guess, outcome);
+            }
+            match server.status() {
+                Status::Won(_) => won += 1,
+                _ => {
+                    assert!(hints.ignored_by(answer).is_some(), "{}", answer);
+                    unwinnable += 1;
+                }
+            }
+        }
+        println!("Won {}, {} left unwinnable by lies", won, unwinnable);
     }
 
     #[test]
     #[ignore]
     fn test_interactive_server() {
-        let dict = load_dictionary();
+        let dict = Dictionary::embedded();
         let mut server = server::InteractiveServer;
         let mut solver = solver::Solver::new(dict);
 
         loop {
-            let (_, outcome) = solver.guess(&mut server).unwrap();
-            if outcome == [LetterOutcome::Correct; 5] {
-                break;
+            match solver.guess(&mut server) {
+                Ok((_, outcome)) if outcome == [LetterOutcome::Correct; 5] => break,
+                Ok(_) => {}
+                // Forget the latest outcome which could be mistyped, its guess may be
+                // suggested again
+                Err(solver::Error::Contradiction(rows)) => {
+                    println!("Outcomes {:?} contradict each other", rows);
+                    let row = *rows.last().expect("No single outcome is mistaken");
+                    solver.retract(row).unwrap();
+                }
+                Err(e) => panic!("{:?}", e),
             }
         }
     }
 
-    fn run_solver(word: Word, dict: HashSet<Word>) -> u8 {
-        let mut server = server::InMemoryServer::new(word, dict.clone());
-        let mut solver = solver::Solver::new(dict);
+    /// Play every word in the dictionary, printing statistics and returning the number of failures.
+    fn benchmark<T>(dict: &Dictionary, solver: solver::Solver<T>) -> u16
+    where
+        T: Strategy + Clone + fmt::Debug,
+    {
+        let mut total: u16 = 0;
+        let mut count: u16 = 0;
+        let mut fail_count: u16 = 0;
+        let mut worst: u8 = 0;
+        for word in dict.answers().iter() {
+            count += 1;
+            let result = run_solver(*word, dict.clone(), solver.clone());
+            total += u16::from(result);
+            worst = worst.max(result);
+            if result > 6 {
+                fail_count += 1;
+            }
+        }
+        let ratio = f64::from(total) / f64::from(count);
+        println!("Average guesses to solve: {}", ratio);
+        let ratio = f64::from(fail_count) / f64::from(count);
+        println!("Failure rate: {}", ratio);
+        println!("Most guesses needed: {}", worst);
+        fail_count
+    }
+
+    fn run_solver<T>(word: Word, dict: Dictionary, mut solver: solver::Solver<T>) -> u8
+    where
+        T: Strategy + fmt::Debug,
+    {
+        let mut server = server::InMemoryServer::new(word, dict).with_rules(solver.rules);
 
         let mut guess_counter = 0u8;
         loop {
@@ -280,19 +1352,14 @@ mod tests {
                         break;
                     }
                 }
-                Err(super::Error::Stumped) => panic!("{:?}\n{:?}", server, solver),
-                Err(_) => {
+                Err(super::Error::Server(server::Error::GameOver)) => {
                     // println!("{:?}", word);
                     guess_counter = 7;
                     break;
                 }
+                Err(e) => panic!("{:?}\n{:?}\n{:?}", e, server, solver),
             }
         }
         guess_counter
     }
-
-    fn load_dictionary() -> HashSet<Word> {
-        let text = std::fs::read_to_string("./res/words.txt").unwrap();
-        text.split('\n').filter_map(Word::try_from_str).collect()
-    }
 }
@@ -0,0 +1,280 @@
+use crate::matrix::{CandidateSet, PatternMatrix};
+use crate::solver::Knowledge;
+use crate::{score, Pattern, Word};
+
+pub trait Strategy {
+    /// Choose the next word to guess.
+    fn select(&mut self, context: &Context) -> Option<Word>;
+
+    /// Whether the solver should play the strategy's choice for the last guess the
+    /// rules allow, instead of the candidate most likely to be the answer.
+    fn chooses_last_guess(&self) -> bool {
+        false
+    }
+}
+
+/// What a strategy can see of the game in progress.
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    pub(crate) matrix: &'a PatternMatrix,
+    pub(crate) candidates: &'a CandidateSet,
+    pub(crate) weights: &'a [f64],
+    pub(crate) guesses: &'a [usize],
+    pub(crate) knowledge: &'a Knowledge,
+}
+
+impl<'a> Context<'a> {
+    pub fn matrix(&self) -> &'a PatternMatrix {
+        self.matrix
+    }
+
+    /// Indices into the matrix answers of the words which could still be the answer.
+    pub fn candidates(&self) -> &'a CandidateSet {
+        self.candidates
+    }
+
+    /// Indices into the matrix guesses of the words which may be played, sorted.
+    pub fn guesses(&self) -> &'a [usize] {
+        self.guesses
+    }
+
+    pub fn knowledge(&self) -> &'a Knowledge {
+        self.knowledge
+    }
+
+    pub fn candidate_words(&self) -> impl Iterator<Item = Word> + 'a {
+        let answers = self.matrix.answers();
+        self.candidates.iter().map(move |i| answers[i])
+    }
+
+    /// The prior likelihood of the answer at index `answer`, relative to the others.
+    pub fn weight(&self, answer: usize) -> f64 {
+        self.weights[answer]
+    }
+
+    /// The total weight of the candidates.
+    pub fn total_weight(&self) -> f64 {
+        self.candidates.iter().map(|a| self.weights[a]).sum()
+    }
+
+    /// The candidate most likely to be the answer, preferring the first on ties. Only
+    /// candidates which may be played are considered.
+    pub fn likeliest(&self) -> Option<Word> {
+        let answers = self.matrix.answers();
+        let playable = |a: usize| {
+            self.matrix
+                .guess_index(&answers[a])
+                .map_or(false, |g| self.guesses.binary_search(&g).is_ok())
+        };
+        let mut best: Option<(usize, f64)> = None;
+        for a in self.candidates.iter().filter(|a| playable(*a)) {
+            if best.map_or(true, |(_, w)| self.weights[a] > w) {
+                best = Some((a, self.weights[a]));
+            }
+        }
+        best.map(|(a, _)| answers[a])
+    }
+
+    /// The weight of the guess at index `guess` if it could be the answer, otherwise zero.
+    pub fn candidate_weight(&self, guess: usize) -> f64 {
+        match self.matrix.guess_answer(guess) {
+            Some(a) if self.candidates.contains(a) => self.weights[a],
+            _ => 0.0,
+        }
+    }
+
+    /// Whether the guess at index `guess` could be the answer.
+    pub fn is_candidate(&self, guess: usize) -> bool {
+        self.matrix
+            .guess_answer(guess)
+            .map_or(false, |a| self.candidates.contains(a))
+    }
+
+    /// Count how many of the candidates produce each pattern for the guess at index `guess`.
+    pub fn buckets(&self, guess: usize) -> [u32; Pattern::COUNT] {
+        let row = self.matrix.row(guess);
+        let mut buckets = [0u32; Pattern::COUNT];
+        for a in self.candidates.iter() {
+            buckets[row[a].index()] += 1;
+        }
+        buckets
+    }
+
+    /// Sum the weights of the candidates producing each pattern for the guess at index `guess`.
+    pub fn weighted_buckets(&self, guess: usize) -> [f64; Pattern::COUNT] {
+        let row = self.matrix.row(guess);
+        let mut buckets = [0.0; Pattern::COUNT];
+        for a in self.candidates.iter() {
+            buckets[row[a].index()] += self.weights[a];
+        }
+        buckets
+    }
+}
+
+/// Choose the word which maximizes the expected information (Shannon entropy)
+/// of the outcome over the remaining candidate answers, weighted by how likely
+/// they are. Ties are broken in favour of likely answers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Entropy;
+
+impl Strategy for Entropy {
+    fn select(&mut self, context: &Context) -> Option<Word> {
+        if context.candidates().len() <= 2 {
+            if let Some(word) = context.likeliest() {
+                return Some(word);
+            }
+        }
+        let total = context.total_weight();
+        best_guess(context, |guess| {
+            entropy(&context.weighted_buckets(guess), total)
+        })
+    }
+}
+
+/// Choose the word which minimizes the number of candidate answers left in the
+/// worst case, bounding how many guesses are needed to finish. Ties are broken
+/// in favour of likely answers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Minimax;
+
+impl Strategy for Minimax {
+    fn select(&mut self, context: &Context) -> Option<Word> {
+        if context.candidates().len() <= 2 {
+            if let Some(word) = context.likeliest() {
+                return Some(word);
+            }
+        }
+        best_guess(context, |guess| {
+            let largest = context.buckets(guess).iter().max().copied().unwrap_or(0);
+            -f64::from(largest)
+        })
+    }
+}
+
+/// Guess the remaining candidate with the most distinct vowels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MostVowels;
+
+impl Strategy for MostVowels {
+    fn select(&mut self, context: &Context) -> Option<Word> {
+        context.candidate_words().max_by_key(Word::distinct_vowels)
+    }
+}
+
+/// The guess with the highest value of `f`. When values are equal, words more likely
+/// to be the answer are preferred, and any candidate to those which cannot be the answer.
+fn best_guess<F>(context: &Context, f: F) -> Option<Word>
+where
+    F: Fn(usize) -> f64,
+{
+    best_guess_index(context.guesses(), |guess| {
+        (f(guess), context.candidate_weight(guess))
+    })
+    .map(|i| context.matrix().guesses()[i])
+}
+
+/// The index of the guess with the highest value, where `f` gives the value of a guess
+/// and how likely it is to win. Likelier guesses are preferred when values are equal.
+pub(crate) fn best_guess_index<F>(guesses: &[usize], f: F) -> Option<usize>
+where
+    F: Fn(usize) -> (f64, f64),
+{
+    let mut best: Option<(usize, f64, f64)> = None;
+    for guess in guesses.iter().copied() {
+        let (value, weight) = f(guess);
+        let better = match best {
+            None => true,
+            Some((_, v, w)) if weight > w => value >= v - tolerance(v),
+            Some((_, v, _)) => value > v + tolerance(v),
+        };
+        if better {
+            best = Some((guess, value, weight));
+        }
+    }
+    best.map(|(i, _, _)| i)
+}
+
+/// How far apart values of about `value` can be and still count as equal, allowing for
+/// rounding in sums like those of `entropy`.
+fn tolerance(value: f64) -> f64 {
+    1e-9 * value.abs().max(1.0)
+}
+
+/// Expected information (in bits) gained from a guess splitting candidates
+/// with weights adding up to `total` into `buckets`.
+pub(crate) fn entropy(buckets: &[f64; Pattern::COUNT], total: f64) -> f64 {
+    let sum: f64 = buckets
+        .iter()
+        .filter(|&&w| w > 0.0)
+        .map(|&w| w * w.log2())
+        .sum();
+    total.log2() - sum / total
+}
+
+/// Count how many of the `candidates` produce each outcome for `guess`.
+pub(crate) fn buckets(guess: &Word, candidates: &[Word]) -> [u32; Pattern::COUNT] {
+    let mut buckets = [0u32; Pattern::COUNT];
+    for answer in candidates {
+        let pattern = Pattern::from(score(*guess, *answer));
+        buckets[pattern.index()] += 1;
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::server::InMemoryServer;
+    use crate::solver::Solver;
+    use crate::strategy::{Entropy, Minimax, MostVowels, Strategy};
+    use crate::util::words;
+    use crate::{LetterOutcome, Word};
+
+    #[test]
+    fn test_strategies_solve() {
+        let dict: Dictionary = words(&[
+            "river", "abbey", "crave", "kings", "great", "trees", "whack", "audio", "snake",
+            "track", "clack", "dwell", "dense", "dryer",
+        ])
+        .into_iter()
+        .collect();
+
+        for word in dict.answers().iter() {
+            assert!(solves(*word, &dict, Entropy));
+            assert!(solves(*word, &dict, Minimax));
+            assert!(solves(*word, &dict, MostVowels));
+        }
+    }
+
+    #[test]
+    fn test_weighted_strategies() {
+        let words = words(&["batch", "catch", "hatch", "latch"]);
+        let uniform = Dictionary::new(words.clone(), vec![]);
+        let weighted = uniform.clone().with_weights(vec![(words[2], 6.0)]).unwrap();
+
+        // With nothing to tell the answers apart, guess the likeliest one
+        assert_eq!(Solver::new(uniform).suggest(), Some(words[0]));
+        assert_eq!(Solver::new(weighted.clone()).suggest(), Some(words[2]));
+        let minimax = Solver::with_strategy(weighted.clone(), Minimax);
+        assert_eq!(minimax.suggest(), Some(words[2]));
+
+        let mut solver = Solver::new(weighted);
+        assert_eq!(solver.probability(&words[2]), 6.0 / 9.0);
+        assert_eq!(solver.probability(&words[0]), 1.0 / 9.0);
+        let outcome = crate::score(words[2], words[3]);
+        solver.observe(words[2], outcome).unwrap();
+        assert_eq!(solver.probability(&words[2]), 0.0);
+        assert_eq!(solver.probability(&words[3]), 1.0 / 3.0);
+    }
+
+    fn solves<T: Strategy>(word: Word, dict: &Dictionary, strategy: T) -> bool {
+        let mut server = InMemoryServer::new(word, dict.clone());
+        let mut solver = Solver::with_strategy(dict.clone(), strategy);
+        while let Ok((_, outcome)) = solver.guess(&mut server) {
+            if outcome == [LetterOutcome::Correct; 5] {
+                return true;
+            }
+        }
+        false
+    }
+}
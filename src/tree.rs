@@ -0,0 +1,785 @@
+use crate::dictionary::Dictionary;
+use crate::matrix::{CandidateSet, PatternMatrix};
+use crate::server::InMemoryServer;
+use crate::solver::{self, Solver};
+use crate::strategy::{Context, Strategy};
+use crate::{outcome_from_str, outcome_to_str, GuessOutcome, LetterOutcome, Pattern, Word};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A complete plan for playing the game: the word to guess now, and the
+/// plan to follow for each outcome that does not win the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionTree {
+    guess: Word,
+    /// Whether the guess is itself one of the answers reaching this node
+    solves: bool,
+    /// Next steps for each outcome other than a win
+    children: Vec<(GuessOutcome, DecisionTree)>,
+}
+
+impl DecisionTree {
+    pub fn guess(&self) -> Word {
+        self.guess
+    }
+
+    pub fn child(&self, outcome: &GuessOutcome) -> Option<&DecisionTree> {
+        self.children
+            .iter()
+            .find(|(o, _)| o == outcome)
+            .map(|(_, t)| t)
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &(GuessOutcome, DecisionTree)> {
+        self.children.iter()
+    }
+
+    /// The total number of guesses needed to solve every answer covered by the tree.
+    pub fn total_guesses(&self) -> u32 {
+        self.cost(1)
+    }
+
+    /// The number of answers the tree solves.
+    pub fn answers(&self) -> u32 {
+        u32::from(self.solves) + self.children.iter().map(|(_, t)| t.answers()).sum::<u32>()
+    }
+
+    /// The largest number of guesses needed to solve any answer covered by the tree.
+    pub fn depth(&self) -> u8 {
+        1 + self
+            .children
+            .iter()
+            .map(|(_, t)| t.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Compute the tree minimizing the expected number of guesses to find an answer
+    /// chosen uniformly from `answers`, only ever playing words from `guesses` and
+    /// never needing more than `max_guesses`. Returns `None` if no such tree exists,
+    /// which includes when some answer is not one of the `guesses`.
+    ///
+    /// Answer weights are deliberately left out: every answer counts equally, so for a
+    /// weighted dictionary the tree is optimal as if its answers were equally likely.
+    ///
+    /// The search time grows steeply with the number of answers: a few hundred take
+    /// seconds, while the full embedded word list takes a few minutes.
+    pub fn optimal(answers: &[Word], guesses: &[Word], max_guesses: u8) -> Option<Self> {
+        let matrix = PatternMatrix::new(guesses.to_vec(), answers.to_vec());
+        // An answer is only found by guessing it
+        let mut search = Search::new(&matrix, max_guesses)?;
+        let answers: Vec<usize> = (0..matrix.answers().len()).collect();
+        // Only trees which beat the greedy one need to be searched for
+        let (bound, greedy) = match search.greedy(&answers, max_guesses) {
+            Some((cost, greedy)) => (cost, Some(greedy)),
+            None => (u32::MAX, None),
+        };
+        match search.solve(&answers, max_guesses, bound) {
+            Some(_) => Some(search.tree(&answers, max_guesses)),
+            None => greedy,
+        }
+    }
+
+    /// Build the tree describing how `solver` plays each answer of the `dictionary`.
+    pub fn play<T>(solver: &Solver<T>, dictionary: &Dictionary) -> Result<Self, Error>
+    where
+        T: Strategy + Clone,
+    {
+        let mut answers: Vec<Word> = dictionary.answers().iter().copied().collect();
+        answers.sort_unstable();
+
+        let mut tree: Option<Self> = None;
+        for answer in answers {
+            let mut server = InMemoryServer::new(answer, dictionary.clone());
+            let mut solver = solver.clone();
+            let mut path = Vec::new();
+            loop {
+                let (guess, outcome) = solver
+                    .guess(&mut server)
+                    .map_err(|e| Error::Solver(answer, e))?;
+                path.push((guess, outcome));
+                if outcome == [LetterOutcome::Correct; 5] {
+                    break;
+                }
+            }
+            match tree {
+                Some(ref mut t) => t.insert(&path)?,
+                None => tree = Some(Self::from_path(&path)?),
+            }
+        }
+        tree.ok_or(Error::Empty)
+    }
+
+    fn leaf(guess: Word) -> Self {
+        Self {
+            guess,
+            solves: true,
+            children: Vec::new(),
+        }
+    }
+
+    fn from_path(path: &[(Word, GuessOutcome)]) -> Result<Self, Error> {
+        let (guess, _) = path.first().ok_or(Error::Empty)?;
+        let mut tree = Self {
+            guess: *guess,
+            solves: false,
+            children: Vec::new(),
+        };
+        tree.insert(path)?;
+        Ok(tree)
+    }
+
+    /// Add the sequence of guesses leading to an answer, which must end with a win.
+    fn insert(&mut self, path: &[(Word, GuessOutcome)]) -> Result<(), Error> {
+        let ((guess, outcome), rest) = path.split_first().ok_or(Error::Incomplete)?;
+        if *guess != self.guess {
+            return Err(Error::Conflict(*guess));
+        }
+        if *outcome == [LetterOutcome::Correct; 5] {
+            return if rest.is_empty() {
+                self.solves = true;
+                Ok(())
+            } else {
+                Err(Error::Incomplete)
+            };
+        }
+
+        let (next_guess, _) = rest.first().ok_or(Error::Incomplete)?;
+        let i = match self
+            .children
+            .binary_search_by_key(&Pattern::from(*outcome), |(o, _)| Pattern::from(*o))
+        {
+            Ok(i) => i,
+            Err(i) => {
+                let child = Self {
+                    guess: *next_guess,
+                    solves: false,
+                    children: Vec::new(),
+                };
+                self.children.insert(i, (*outcome, child));
+                i
+            }
+        };
+        self.children[i].1.insert(rest)
+    }
+
+    fn write_paths(&self, prefix: &mut String, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.solves {
+            writeln!(f, "{}{} *****", prefix, self.guess)?;
+        }
+        for (outcome, child) in self.children.iter() {
+            let len = prefix.len();
+            prefix.push_str(&format!("{} {} ", self.guess, outcome_to_str(outcome)));
+            child.write_paths(prefix, f)?;
+            prefix.truncate(len);
+        }
+        Ok(())
+    }
+
+    fn cost(&self, depth: u32) -> u32 {
+        let solved_here = if self.solves { depth } else { 0 };
+        solved_here
+            + self
+                .children
+                .iter()
+                .map(|(_, t)| t.cost(depth + 1))
+                .sum::<u32>()
+    }
+}
+
+/// The tree is written with one line per answer, listing each guess followed by its
+/// outcome (`*` correct, `+` present, `-` absent), e.g. `raise --+-- pilot *****`.
+impl fmt::Display for DecisionTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_paths(&mut String::new(), f)
+    }
+}
+
+/// Parse the format written by `Display`. Blank lines and lines starting with `#` are ignored.
+impl FromStr for DecisionTree {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tree: Option<Self> = None;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = || Error::Parse { line: i + 1 };
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let path = tokens
+                .chunks(2)
+                .map(|pair| match pair {
+                    [w, o] => Some((Word::try_from_str(w)?, outcome_from_str(o)?)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(parse_error)?;
+            let result = match tree {
+                Some(ref mut t) => t.insert(&path),
+                None => Self::from_path(&path).map(|t| tree = Some(t)),
+            };
+            result.map_err(|_| parse_error())?;
+        }
+        tree.ok_or(Error::Empty)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The solver could not find this answer
+    Solver(Word, solver::Error),
+    /// Different guesses were made after the same sequence of outcomes
+    Conflict(Word),
+    /// A sequence of guesses did not end with a win
+    Incomplete,
+    /// A line of text was malformed or inconsistent with the lines before it
+    Parse { line: usize },
+    /// There were no answers to build a tree from
+    Empty,
+}
+
+/// A tree along with the total number of guesses it needs.
+type Solution = (u32, DecisionTree);
+
+/// The cost of the optimal tree for a set of answers with its first guess, or a lower
+/// bound on the cost when no tree as cheap as the bound searched for was found.
+type Memo = HashMap<CandidateSet, Result<(u32, usize), u32>>;
+
+/// Branch-and-bound search for optimal decision trees over the words of a matrix,
+/// with sets of answers given by their indices into the matrix answers. Every answer
+/// is one of the guesses, so the answers themselves may be played.
+struct Search<'a> {
+    matrix: &'a PatternMatrix,
+    /// The index into the matrix guesses of each matrix answer
+    answer_guesses: Vec<usize>,
+    /// Results for sets of answers already searched, indexed by the number of guesses left
+    memo: Vec<Memo>,
+}
+
+impl<'a> Search<'a> {
+    /// Prepare to search the words of `matrix`, returning `None` if some answer is not
+    /// one of the guesses.
+    fn new(matrix: &'a PatternMatrix, max_guesses: u8) -> Option<Self> {
+        let answer_guesses = matrix
+            .answers()
+            .iter()
+            .map(|a| matrix.guess_index(a))
+            .collect::<Option<_>>()?;
+        Some(Self {
+            matrix,
+            answer_guesses,
+            memo: vec![HashMap::new(); usize::from(max_guesses) + 1],
+        })
+    }
+
+    /// The memo key for a set of answers.
+    fn key(&self, answers: &[usize]) -> CandidateSet {
+        let mut set = CandidateSet::empty(self.matrix.answers().len());
+        for a in answers {
+            set.insert(*a);
+        }
+        set
+    }
+
+    /// Count how many of the `answers` produce each pattern for the guess at index `guess`.
+    fn buckets(&self, guess: usize, answers: &[usize]) -> [u32; Pattern::COUNT] {
+        let mut buckets = [0u32; Pattern::COUNT];
+        // The matrix has already grouped the whole answer list
+        if answers.len() == self.matrix.answers().len() {
+            for p in Pattern::all() {
+                buckets[p.index()] = self.matrix.answers_with(guess, p).len() as u32;
+            }
+        } else {
+            let row = self.matrix.row(guess);
+            for a in answers {
+                buckets[row[*a].index()] += 1;
+            }
+        }
+        buckets
+    }
+
+    /// Group the `answers` by the pattern they produce for the guess at index `guess`,
+    /// leaving out the one which wins.
+    fn partition(&self, guess: usize, answers: &[usize]) -> Vec<(Pattern, Vec<usize>)> {
+        if answers.len() == self.matrix.answers().len() {
+            return Pattern::all()
+                .filter(|p| !p.is_win())
+                .map(|p| (p, self.matrix.answers_with(guess, p).collect::<Vec<_>>()))
+                .filter(|(_, b)| !b.is_empty())
+                .collect();
+        }
+        let row = self.matrix.row(guess);
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); Pattern::COUNT];
+        for a in answers {
+            buckets[row[*a].index()].push(*a);
+        }
+        Pattern::all()
+            .zip(buckets)
+            .filter(|(p, b)| !p.is_win() && !b.is_empty())
+            .collect()
+    }
+
+    /// Whether the guess at index `guess` is one of the `answers` and every other answer
+    /// then needs exactly one more guess.
+    fn separates(&self, guess: usize, answers: &[usize]) -> bool {
+        let row = self.matrix.row(guess);
+        let mut seen = [false; Pattern::COUNT];
+        let mut solves = false;
+        for a in answers {
+            let p = row[*a].index();
+            solves |= row[*a].is_win();
+            if seen[p] {
+                return false;
+            }
+            seen[p] = true;
+        }
+        solves
+    }
+
+    /// An answer separating all the others, as the index of its guess.
+    fn separating_guess(&self, answers: &[usize]) -> Option<usize> {
+        answers
+            .iter()
+            .map(|a| self.answer_guesses[*a])
+            .find(|g| self.separates(*g, answers))
+    }
+
+    /// The guesses which tell some of the `answers` apart, each with a lower bound on
+    /// the cost of playing it first with `depth` guesses left.
+    fn options(&self, answers: &[usize], depth: u8) -> Vec<(u32, usize)> {
+        let n = answers.len() as u32;
+        let win = Pattern::WIN.index();
+        (0..self.matrix.guesses().len())
+            .filter_map(|guess| {
+                let buckets = self.buckets(guess, answers);
+                if buckets.contains(&n) && buckets[win] != n {
+                    // guess gives no information
+                    return None;
+                }
+                let lb = buckets
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != win)
+                    .fold(n, |lb, (_, k)| {
+                        lb.saturating_add(lower_bound(*k, depth - 1))
+                    });
+                Some((lb, guess))
+            })
+            .collect()
+    }
+
+    /// A lower bound on the cost of solving `answers` with `depth` guesses left, from
+    /// the best any single guess could do with them.
+    fn bound(&mut self, answers: &[usize], depth: u8) -> u32 {
+        let n = answers.len() as u32;
+        if n <= 2 || depth <= 1 || self.separating_guess(answers).is_some() {
+            return lower_bound(n, depth);
+        }
+        let key = self.key(answers);
+        match self.memo[usize::from(depth)].get(&key) {
+            Some(Ok((cost, _))) => *cost,
+            Some(Err(lb)) => *lb,
+            None => {
+                let options = self.options(answers, depth);
+                let lb = options
+                    .iter()
+                    .map(|(lb, _)| *lb)
+                    .min()
+                    .unwrap_or(UNSOLVABLE);
+                self.memo[usize::from(depth)].insert(key, Err(lb));
+                lb
+            }
+        }
+    }
+
+    /// Find the cost of the cheapest tree for `answers` using at most `depth` guesses,
+    /// provided it is less than `bound`.
+    fn solve(&mut self, answers: &[usize], depth: u8, bound: u32) -> Option<u32> {
+        let n = answers.len() as u32;
+        let lb = lower_bound(n, depth);
+        if n == 0 || lb >= bound {
+            return None;
+        }
+        // Guessing either of two answers first tells them apart, and if one of the
+        // answers separates all the others nothing can do better
+        if n <= 2 || self.separating_guess(answers).is_some() {
+            return Some(lb);
+        }
+
+        let key = self.key(answers);
+        match self.memo[usize::from(depth)].get(&key) {
+            Some(Ok((cost, _))) => return if *cost < bound { Some(*cost) } else { None },
+            Some(Err(lb)) if *lb >= bound => return None,
+            _ => (),
+        }
+
+        // Try the guesses with the most promising lower bounds first so that
+        // good solutions are found early and prune the rest of the search.
+        let mut options = self.options(answers, depth);
+        options.sort_unstable();
+        let least = options.first().map_or(UNSOLVABLE, |(lb, _)| *lb);
+
+        let mut best: Option<(u32, usize)> = None;
+        let mut limit = bound;
+        for (lb, guess) in options {
+            if lb >= limit {
+                break;
+            }
+            // Solve the largest groups first, they are the most likely to exceed the limit
+            let mut partition = self.partition(guess, answers);
+            partition.sort_by_key(|(_, b)| std::cmp::Reverse(b.len()));
+            // Looking one guess ahead in every group is much cheaper than solving any
+            // of them, and rules out most guesses
+            let mut cost = lb;
+            let mut bounds = Vec::with_capacity(partition.len());
+            for (_, bucket) in partition.iter() {
+                let bucket_lb = self.bound(bucket, depth - 1);
+                cost =
+                    (cost - lower_bound(bucket.len() as u32, depth - 1)).saturating_add(bucket_lb);
+                bounds.push(bucket_lb);
+                if cost >= limit {
+                    break;
+                }
+            }
+            if cost >= limit {
+                continue;
+            }
+            for ((_, bucket), bucket_lb) in partition.iter().zip(bounds) {
+                match self.solve(bucket, depth - 1, limit - (cost - bucket_lb)) {
+                    Some(c) => cost = cost - bucket_lb + c,
+                    None => {
+                        cost = limit;
+                        break;
+                    }
+                }
+            }
+            if cost < limit {
+                limit = cost;
+                best = Some((cost, guess));
+            }
+        }
+
+        let entry = best.ok_or_else(|| bound.max(least));
+        self.memo[usize::from(depth)].insert(key, entry);
+        best.map(|(cost, _)| cost)
+    }
+
+    /// Build the tree for `answers` found by solving them with `depth` guesses left.
+    fn tree(&self, answers: &[usize], depth: u8) -> DecisionTree {
+        let guess = match answers {
+            [a] => return DecisionTree::leaf(self.matrix.answers()[*a]),
+            [a, _] => self.answer_guesses[*a],
+            _ => match self.separating_guess(answers) {
+                Some(guess) => guess,
+                None => match self.memo[usize::from(depth)].get(&self.key(answers)) {
+                    Some(Ok((_, guess))) => *guess,
+                    _ => unreachable!("Answers which were not solved"),
+                },
+            },
+        };
+        DecisionTree {
+            guess: self.matrix.guesses()[guess],
+            solves: self.solves(guess, answers),
+            children: self
+                .partition(guess, answers)
+                .into_iter()
+                .map(|(p, bucket)| (p.outcome(), self.tree(&bucket, depth - 1)))
+                .collect(),
+        }
+    }
+
+    /// Whether the word at index `guess` is one of the `answers`.
+    fn solves(&self, guess: usize, answers: &[usize]) -> bool {
+        let row = self.matrix.row(guess);
+        answers.iter().any(|a| row[*a].is_win())
+    }
+
+    /// The tree which always plays the guess splitting the answers most evenly,
+    /// preferring guesses which could win, if it needs at most `depth` guesses.
+    fn greedy(&self, answers: &[usize], depth: u8) -> Option<Solution> {
+        let n = answers.len() as u32;
+        if depth == 0 || n == 0 {
+            return None;
+        }
+        if n == 1 {
+            return Some((1, DecisionTree::leaf(self.matrix.answers()[answers[0]])));
+        }
+
+        let win = Pattern::WIN.index();
+        let mut best: Option<(f64, bool, usize)> = None;
+        for guess in 0..self.matrix.guesses().len() {
+            let buckets = self.buckets(guess, answers);
+            if buckets.contains(&n) && buckets[win] != n {
+                continue;
+            }
+            // Lower is more even, as entropy is log n minus this over n
+            let spread: f64 = buckets
+                .iter()
+                .filter(|&&k| k > 1)
+                .map(|&k| f64::from(k) * f64::from(k).log2())
+                .sum();
+            let solves = buckets[win] > 0;
+            let better = match best {
+                None => true,
+                Some((s, w, _)) => spread < s - 1e-9 || (spread < s + 1e-9 && solves && !w),
+            };
+            if better {
+                best = Some((spread, solves, guess));
+            }
+        }
+
+        let (_, _, guess) = best?;
+        let mut cost = n;
+        let mut children = Vec::new();
+        for (p, bucket) in self.partition(guess, answers) {
+            let (c, tree) = self.greedy(&bucket, depth - 1)?;
+            cost += c;
+            children.push((p.outcome(), tree));
+        }
+        Some((
+            cost,
+            DecisionTree {
+                guess: self.matrix.guesses()[guess],
+                solves: self.solves(guess, answers),
+                children,
+            },
+        ))
+    }
+}
+
+/// The cost of answers which cannot be solved in the guesses left.
+const UNSOLVABLE: u32 = u32::MAX;
+
+/// The cheapest conceivable cost of solving `n` answers with `depth` guesses left: the
+/// first guess finds one of them and every other is found with the second guess.
+fn lower_bound(n: u32, depth: u8) -> u32 {
+    match (n, depth) {
+        (0, _) => 0,
+        (_, 0) => UNSOLVABLE,
+        (1, _) => 1,
+        (_, 1) => UNSOLVABLE,
+        _ => 2 * n - 1,
+    }
+}
+
+/// Play by looking up the next guess in the tree, following the outcomes seen so far.
+impl Strategy for DecisionTree {
+    fn select(&mut self, context: &Context) -> Option<Word> {
+        let mut node = &*self;
+        for (guess, outcome) in context.knowledge().outcomes() {
+            if *guess != node.guess {
+                return None;
+            }
+            node = node.child(outcome)?;
+        }
+        Some(node.guess)
+    }
+
+    /// The tree already plans for the number of guesses it was built for.
+    fn chooses_last_guess(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::Dictionary;
+    use crate::rules::GameRules;
+    use crate::server::InMemoryServer;
+    use crate::solver::Solver;
+    use crate::tree::{self, DecisionTree};
+    use crate::util::words;
+    use crate::{score, LetterOutcome, Pattern, Word};
+    use std::collections::BTreeMap;
+
+    const WORDS: [&str; 14] = [
+        "fight", "light", "might", "night", "right", "sight", "tight", "eight", "flint", "mould",
+        "wrong", "shirt", "grand", "stamp",
+    ];
+
+    #[test]
+    fn test_optimal_tree() {
+        let words = words(&WORDS);
+        let tree = DecisionTree::optimal(&words, &words, 6).unwrap();
+
+        assert_eq!(tree.answers(), words.len() as u32);
+        assert!(tree.depth() <= 6);
+        assert_eq!(
+            Some(tree.total_guesses()),
+            exhaustive_cost(&words, &words, 6)
+        );
+
+        // Tighter limits can only make the tree worse, or impossible
+        let mut max_guesses = tree.depth();
+        while let Some(limited) = DecisionTree::optimal(&words, &words, max_guesses) {
+            assert!(limited.total_guesses() >= tree.total_guesses());
+            assert_eq!(
+                Some(limited.total_guesses()),
+                exhaustive_cost(&words, &words, max_guesses)
+            );
+            max_guesses -= 1;
+        }
+        assert_eq!(exhaustive_cost(&words, &words, max_guesses), None);
+
+        // An answer which may not be guessed can never be found
+        let allowed: Vec<Word> = words[1..].to_vec();
+        assert_eq!(DecisionTree::optimal(&words, &allowed, 6), None);
+
+        // Neighbouring words in the dictionary share prefixes, so are hard to separate
+        let mut dict: Vec<Word> = Dictionary::embedded().iter().copied().collect();
+        dict.sort_unstable();
+        for group in dict.chunks(10).step_by(23) {
+            for max_guesses in [3, 6] {
+                let tree = DecisionTree::optimal(group, group, max_guesses);
+                assert_eq!(
+                    tree.map(|t| t.total_guesses()),
+                    exhaustive_cost(group, group, max_guesses)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_round_trip() {
+        let dict: Dictionary = words(&WORDS).into_iter().collect();
+        let solver = Solver::new(dict.clone());
+        let tree = DecisionTree::play(&solver, &dict).unwrap();
+        assert_eq!(tree.answers(), dict.answers().len() as u32);
+
+        let text = tree.to_string();
+        assert_eq!(text.lines().count(), dict.answers().len());
+        let loaded: DecisionTree = text.parse().unwrap();
+        assert_eq!(loaded, tree);
+
+        // The loaded tree plays exactly like the strategy it was built from
+        let replayed = DecisionTree::play(&Solver::with_strategy(dict.clone(), loaded), &dict);
+        assert_eq!(replayed, Ok(tree));
+    }
+
+    #[test]
+    fn test_tree_parse_errors() {
+        let text = "\
+            # comment
+            mould -+--- fight *****
+
+            mould -+--- light *****
+        ";
+        assert_eq!(
+            text.parse::<DecisionTree>(),
+            Err(tree::Error::Parse { line: 4 })
+        );
+
+        let text = "mould ----- fight *****\nmould ----+ night\n";
+        assert_eq!(
+            text.parse::<DecisionTree>(),
+            Err(tree::Error::Parse { line: 2 })
+        );
+
+        let text = "mould ----- fight *****\nmould ---?- night *****\n";
+        assert_eq!(
+            text.parse::<DecisionTree>(),
+            Err(tree::Error::Parse { line: 2 })
+        );
+
+        assert_eq!("# nothing".parse::<DecisionTree>(), Err(tree::Error::Empty));
+    }
+
+    #[test]
+    fn test_tree_strategy() {
+        let dict: Dictionary = words(&WORDS).into_iter().collect();
+        let words: Vec<Word> = dict.answers().iter().copied().collect();
+        let tree = DecisionTree::optimal(&words, &words, 6).unwrap();
+
+        let mut total = 0;
+        for word in dict.answers().iter() {
+            let mut server = InMemoryServer::new(*word, dict.clone());
+            let mut solver = Solver::with_strategy(dict.clone(), tree.clone());
+            let mut node = &tree;
+            loop {
+                let (guess, outcome) = solver.guess(&mut server).unwrap();
+                assert_eq!(guess, node.guess());
+                total += 1;
+                if outcome == [LetterOutcome::Correct; 5] {
+                    break;
+                }
+                node = node.child(&outcome).unwrap();
+            }
+        }
+        assert_eq!(total, tree.total_guesses());
+
+        // The tree's guesses are played even when the rules allow fewer guesses than
+        // it needs, rather than the first word which could be the answer
+        let (outcome, child) = tree
+            .children()
+            .find(|(o, t)| {
+                let first = words
+                    .iter()
+                    .filter(|w| score(tree.guess(), **w) == *o)
+                    .min();
+                first != Some(&t.guess())
+            })
+            .unwrap();
+        let two_guesses = GameRules {
+            max_guesses: Some(2),
+            ..GameRules::default()
+        };
+        let mut solver = Solver::with_strategy(dict, tree.clone()).with_rules(two_guesses);
+        solver.observe(tree.guess(), *outcome).unwrap();
+        assert_eq!(solver.guesses_left(), Some(1));
+        assert_eq!(solver.suggest(), Some(child.guess()));
+    }
+
+    // Takes a few minutes, run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_optimal_tree_large() {
+        let dict = Dictionary::embedded();
+        let words: Vec<Word> = dict.iter().copied().collect();
+        let tree = DecisionTree::optimal(&words, &words, 6).unwrap();
+        assert_eq!(tree.answers(), words.len() as u32);
+        assert_eq!(tree.guess(), Word::try_from_str("slate").unwrap());
+        assert_eq!(tree.total_guesses(), 7973);
+        let solver = DecisionTree::play(&Solver::new(dict.clone()), &dict).unwrap();
+        assert!(solver.total_guesses() > tree.total_guesses());
+        println!("Opening guess: {:?}", tree.guess());
+        println!(
+            "Average guesses to solve: {}",
+            f64::from(tree.total_guesses()) / f64::from(tree.answers())
+        );
+        println!("Most guesses needed: {}", tree.depth());
+    }
+
+    /// Optimal cost found by trying every guess, without any pruning.
+    fn exhaustive_cost(answers: &[Word], guesses: &[Word], depth: u8) -> Option<u32> {
+        match answers.len() {
+            0 => return Some(0),
+            _ if depth == 0 => return None,
+            1 => return Some(1),
+            _ => (),
+        }
+        guesses
+            .iter()
+            .filter_map(|guess| {
+                let mut cost = answers.len() as u32;
+                let mut buckets: BTreeMap<Pattern, Vec<Word>> = BTreeMap::new();
+                for answer in answers {
+                    let pattern = Pattern::from(score(*guess, *answer));
+                    buckets.entry(pattern).or_default().push(*answer);
+                }
+                for (pattern, bucket) in buckets {
+                    if pattern.is_win() {
+                        continue;
+                    }
+                    if bucket.len() == answers.len() {
+                        return None;
+                    }
+                    cost += exhaustive_cost(&bucket, guesses, depth - 1)?;
+                }
+                Some(cost)
+            })
+            .min()
+    }
+}